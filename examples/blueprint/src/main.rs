@@ -12,66 +12,15 @@
 
 extern crate word_map;
 
-// use word_map::block::Block;
 use rand::Rng;
-use word_map::{block::Block, grid::Grid, Orientation, Point2d};
+use word_map::grid::Grid;
+use word_map::layout::{LayoutOptions, Word};
+use word_map::scale::{ScaleMode, SizeScale};
+use word_map::svg::{self, RenderOptions};
 
 static WIDTH: f32 = 800f32;
 static HEIGHT: f32 = 600f32;
 
-fn render_block(b: &Block) {
-    // rec width is not text width.
-    let rec_width = b.top_right.x - b.bottom_left.x;
-    // rec_height is not text height.
-    let rec_height = b.bottom_left.y - b.top_right.y;
-
-    // println!(
-    //     "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
-    //     b.bottom_left.x, b.bottom_left.y - rec_height, rec_width, rec_height
-    // );
-
-    // Want dots ontop of rectangle.
-    println!(
-        "<circle class=\"bl\" cx=\"{}\" cy=\"{}\" r=\"2\" />",
-        b.bottom_left.x, b.bottom_left.y
-    );
-    println!(
-        "<circle class=\"tr\" cx=\"{}\" cy=\"{}\" r=\"2\" />",
-        b.top_right.x, b.top_right.y
-    );
-
-    match b.orientation {
-        Orientation::Horizontal => {
-            println!(
-                "<text transform=\"translate({}, {}) rotate(0)\" font-size=\"{}\" >{}</text>",
-                b.bottom_left.x, b.bottom_left.y, rec_height, b.text
-            );
-        }
-        Orientation::Vertical90 => {
-            // origin is top left
-            let top_left = Point2d {
-                x: b.top_right.x - rec_width,
-                y: b.top_right.y,
-            };
-            println!(
-              "<text transform=\"translate({}, {}) rotate(90)\" fill=\"\" font-size=\"{}\" >{}</text>",
-              top_left.x, top_left.y, rec_width, b.text
-          );
-        }
-        Orientation::Vertical270 => {
-            // origin is bottom right
-            let bottom_right = Point2d {
-                x: b.bottom_left.x + rec_width,
-                y: b.bottom_left.y,
-            };
-            println!(
-                "<text transform=\"translate({}, {}) rotate(270)\" font-size=\"{}\" >{}</text>",
-                bottom_right.x, bottom_right.y, rec_width, b.text
-            );
-        }
-    }
-}
-
 fn main() {
     use random_word::Lang;
     let mut rng = rand::thread_rng();
@@ -79,19 +28,52 @@ fn main() {
     let mut grid = Grid::new(WIDTH, HEIGHT);
 
     // Limit to bounding rectangle
-    // grid.bounding_rectangle_set(
+    // grid.bounding_rectangle_set(Rect::new(
     //     WIDTH / 3_f32,
     //     2_f32 * WIDTH / 3_f32,
     //     HEIGHT / 3_f32,
     //     2_f32 * HEIGHT / 3_f32,
-    // );
+    // ));
+
+    // Assign a random weight to a word selected at random
+    let words = (0..900)
+        .map(|_| {
+            // input range 1..=10 ( no zero width )
+            let weight = rng.gen_range(1_u32..=10_u32);
+            let text = random_word::gen(Lang::En).to_uppercase();
+            Word::new(text, weight)
+        })
+        .collect::<Vec<_>>();
+
+    // Area grows linearly with weight, so font size grows with its square root.
+    let options = LayoutOptions {
+        size_scale: SizeScale::new(ScaleMode::Sqrt).font_size_range_set([12_f32, 40_f32]),
+        ..LayoutOptions::default()
+    };
+    grid.layout(&words, &options);
+
+    // Want dots ontop of rectangle, and no empty border.
+    let render_options = RenderOptions {
+        show_anchors: true,
+        crop: Some(10_f32),
+        ..RenderOptions::default()
+    };
+    let view_box = svg::view_box(&grid.blocks, &grid.canvas(), &render_options);
 
     println!("<?xml version=\"1.0\" standalone=\"no\"?><!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">
     <svg version=\"1.1\"
-      width=\"{WIDTH}\"
-      height=\"{HEIGHT}\"
+      width=\"{}\"
+      height=\"{}\"
+      viewBox=\"{} {} {} {}\"
       xmlns=\"http://www.w3.org/2000/svg\"
-    >");
+    >",
+        view_box.width(),
+        view_box.height(),
+        view_box.xmin,
+        view_box.ymin,
+        view_box.width(),
+        view_box.height()
+    );
     println!(
         r"<defs>
             <style><![CDATA[
@@ -124,19 +106,8 @@ fn main() {
             ]]></style>
           </defs>"
     );
-    // Assign a random number to a word selected at random
-    for _ in 0..900 {
-        // input range 1..10 ( no zero width )
-        //
-        // assuming a char width of 24px
-        // maps to a screen area based on 24x24 squares
-        let area = 24_f32 * 24_f32 * rng.gen_range(1_f32..10_f32);
-        let text = random_word::gen(Lang::En).to_uppercase();
-        grid.place_block(&text, area);
-    }
-
-    for b in grid.blocks {
-        render_block(&b);
+    for b in &grid.blocks {
+        println!("{}", svg::block(b, &render_options));
     }
     println!(r"</svg>");
 
@@ -6,10 +6,11 @@ extern crate word_map;
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
-fn update(scale: f32, tw: &str) -> String {
+fn update(scale: f32, tw: &str, colors: Vec<String>) -> String {
     use word_map::block::Blocks;
     use word_map::grid::Grid;
     use word_map::grid::TextWeight;
+    use word_map::style::Style;
 
     static WIDTH: f32 = 800_f32;
     static HEIGHT: f32 = 600_f32;
@@ -17,18 +18,18 @@ fn update(scale: f32, tw: &str) -> String {
     // generate_word_map
 
     // TODO:
-    // Make grid  thread safe!!!
-    //
-    // Grid contains ThreadRng
-    // ThredRnd contains Rc<UnsafeCell<ReseedingRng<Core, OsRng>>>
-    //
-    // I want the grid here  to be in a lazy static but I need to first find a
-    // diffent RNG generate.
+    // Grid now holds a StdRng and is Send, so it could live in a lazy static.
     match Grid::parse_pairs(tw) {
         Ok((_, pairs)) => {
             let mut grid = Grid::new(WIDTH, HEIGHT);
+            // A colour, when given, is attached to the word in the same position.
+            let mut colors = colors.into_iter();
             for TextWeight(text, weight) in pairs {
-                grid.place_block(text, scale * weight as f32);
+                let area = scale * weight as f32;
+                match colors.next() {
+                    Some(color) => grid.place_block_with_style(text, area, Style::fill(color)),
+                    None => grid.place_block(text, area),
+                };
             }
             let b = Blocks(grid.blocks);
 
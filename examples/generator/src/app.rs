@@ -1,12 +1,20 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
 use leptos::component;
 use leptos::view;
 use leptos::IntoView;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::JsValue;
-use word_map::block::Block;
-use word_map::Orientation;
-use word_map::Point2d;
+use word_map::color::hex;
+use word_map::svg::RenderOptions;
+use word_map::transition::Transition;
+use word_map::view::render_block;
+
+// Frames drawn when moving from one layout to the next.
+static FRAMES: u32 = 30;
 
 #[wasm_bindgen]
 extern "C" {
@@ -19,72 +27,14 @@ pub(crate) struct UpdateArgs<'a> {
     pub(crate) scale: f32,
     // (text,weight) expressed as a string
     pub(crate) tw: &'a str,
-}
-
-fn render_block(b: &Block, text_fill: &String) -> impl IntoView {
-    // rec width is not text width.
-    let rec_width = b.top_right.x - b.bottom_left.x;
-    // rec_height is not text height.
-    let rec_height = b.bottom_left.y - b.top_right.y;
-
-    let text = match b.orientation {
-        Orientation::Horizontal => {
-            let transform = format!(
-                "translate({}, {}) rotate(0)",
-                b.bottom_left.x, b.bottom_left.y
-            );
-
-            view! {
-                <text transform=transform fill=text_fill font-size=rec_height>
-                    {b.text.clone()}
-                </text>
-            }
-        }
-        Orientation::Vertical90 => {
-            // origin is top left
-            let top_left = Point2d {
-                x: b.top_right.x - rec_width,
-                y: b.top_right.y,
-            };
-            let transform = format!("translate({}, {}) rotate(90)", top_left.x, top_left.y);
-            view! {
-                <text transform=transform fill=text_fill font-size=rec_width>
-                    {b.text.clone()}
-                </text>
-            }
-        }
-        Orientation::Vertical270 => {
-            // origin is bottom right
-            let bottom_right = Point2d {
-                x: b.bottom_left.x + rec_width,
-                y: b.bottom_left.y,
-            };
-            let transform = format!(
-                "translate({}, {}) rotate(270)",
-                bottom_right.x, bottom_right.y
-            );
-            view! {
-                <text transform=transform fill=text_fill font-size=rec_width>
-                    {b.text.clone()}
-                </text>
-            }
-        }
-    };
-
-    // let rect_x = b.bottom_left.x;
-    // let rect_y = b.bottom_left.y - rec_height;
-    view! {
-        // <rect x=rect_x y=rect_y width=rec_width height=rec_height></rect>
-        // <circle class="bl" cx=b.bottom_left.x cy=b.bottom_left.y r="2"></circle>
-        // <circle class="tr" cx=b.top_right.x cy=b.top_right.y r="2"></circle>
-        {text}
-    }
-
+    // The colour of each word in tw, expressed as hex codes.
+    pub(crate) colors: Vec<String>,
 }
 
 #[component]
 pub fn App() -> impl IntoView {
     use leptos::leptos_dom::ev::SubmitEvent;
+    use leptos::leptos_dom::helpers::IntervalHandle;
     use leptos::*;
     use rand::Rng;
     use random_word::Lang;
@@ -105,6 +55,9 @@ pub fn App() -> impl IntoView {
     // List of SVG elements representing the block, expressed as a single string.
     let (blocks, blocks_set) = create_signal::<Vec<Block>>(vec![]);
 
+    // The animation, if any, between the previous and the latest layout.
+    let animation = store_value(None::<IntervalHandle>);
+
     let prepare_text_weights = move |ev| {
         let v = event_target_value(&ev);
         app_state.text_weights_signal.1.set(v);
@@ -121,13 +74,35 @@ pub fn App() -> impl IntoView {
             let args = to_value(&UpdateArgs {
                 scale: app_state.scale_signal.0.get_untracked() as f32,
                 tw: &tw,
+                colors: app_state.color_signal.0.get_untracked(),
             })
             .unwrap();
 
             // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
             let blocks_string: String = invoke("update", args).await.as_string().unwrap();
             let received_blocks: Blocks = serde_json::from_str(&blocks_string).unwrap();
-            blocks_set.set(received_blocks.0);
+
+            // Slide the words shared by both layouts into place.
+            if let Some(handle) = animation.get_value() {
+                handle.clear();
+            }
+            let transition = Transition::new(&blocks.get_untracked(), &received_blocks.0);
+            let frame = Rc::new(Cell::new(0_u32));
+            let handle = set_interval_with_handle(
+                move || {
+                    let n = frame.get() + 1;
+                    frame.set(n);
+                    blocks_set.set(transition.frame(n as f32 / FRAMES as f32));
+                    if n >= FRAMES {
+                        if let Some(handle) = animation.get_value() {
+                            handle.clear();
+                        }
+                    }
+                },
+                Duration::from_millis(16),
+            )
+            .ok();
+            animation.set_value(handle);
         });
     };
 
@@ -183,25 +158,13 @@ pub fn App() -> impl IntoView {
                         <style>{CSS}</style>
                     </defs>
 
+                    // The colour of each word travels with its block.
                     {move || {
-                        let color = app_state.color_signal.0.get();
-                        let ibc = blocks.get().into_iter().enumerate().zip(color);
-                        view! {
-                            // ibc - Index/Block/Color
-
-                            <For
-                                each=move || ibc.clone()
-                                key=|((i, _block), _color)| { *i }
-                                let:data
-                            >
-
-                                {
-                                    let ((_, ref block), ref color) = data;
-                                    render_block(block, color)
-                                }
-
-                            </For>
-                        }
+                        blocks
+                            .get()
+                            .iter()
+                            .map(|block| render_block(block, None, &RenderOptions::default()))
+                            .collect_view()
                     }}
 
                 </svg>
@@ -235,7 +198,7 @@ pub fn App() -> impl IntoView {
                             for _ in 0..100 {
                                 let area = rng.gen_range(10u32..100u32);
                                 let text = random_word::gen(Lang::En).to_uppercase();
-                                colors.push(color_map.rgb(area as f64));
+                                colors.push(hex(color_map.value(area as f64)));
                                 text_weights.push_str(&format!("{text},{area} "));
                             }
                             app_state.text_weights_signal.1.set(text_weights);
@@ -2,15 +2,15 @@ use leptos::create_signal;
 use leptos::ReadSignal;
 use leptos::WriteSignal;
 use word_map::block::Blocks;
-
-use crate::color_map::ColorMap;
+use word_map::color::colorous;
+use word_map::color::{ColorBy, ColorMap, Scheme};
 
 /// State required by all pages.
 #[derive(Clone)]
 pub(crate) struct AppState {
   // Values returned form the server
   pub(crate) blocks: (ReadSignal<Blocks>, WriteSignal<Blocks>),
-  // The colors assigned to the text/weight blocks (expressed as hex codes).
+  // The colors assigned to the text/weight pairs, in order (expressed as hex codes).
   pub(crate) color_signal: (ReadSignal<Vec<String>>, WriteSignal<Vec<String>>),
   // Converts size of block to a color.
   pub(crate) color_map_signal: (ReadSignal<ColorMap>, WriteSignal<ColorMap>),
@@ -25,7 +25,10 @@ impl Default for AppState {
         Self {
           blocks: create_signal::<Blocks>(Blocks::default()),
           color_signal: create_signal::<Vec<String>>(vec![]),
-          color_map_signal: create_signal(ColorMap::new([5f64, 55f64], colorous::PLASMA)),
+          color_map_signal: create_signal(
+            ColorMap::new(Scheme::Gradient(colorous::PLASMA), ColorBy::Weight)
+                .domain_set([5f64, 55f64]),
+          ),
           scale_signal: create_signal(10_u16),
           text_weights_signal: create_signal(String::default()),
         }
@@ -2,6 +2,8 @@ use leptos::component;
 use leptos::view;
 use leptos::IntoView;
 
+use word_map::color::hex;
+
 use crate::app_state::AppState;
 
 /// ColorScale
@@ -33,7 +35,7 @@ pub fn GradientBar(#[prop(default = "PALLETTE")] title: &'static str) -> impl In
                                     width="1"
                                     height="3"
                                     fill=move || {
-                                        app_state.color_map_signal.0.get().rgb(f64::from(pos))
+                                        hex(app_state.color_map_signal.0.get().value(f64::from(pos)))
                                     }
                                 >
                                 </rect>
@@ -46,6 +46,7 @@ pub fn ScaleBar() -> impl IntoView {
                                     &UpdateArgs {
                                         scale: scale as f32,
                                         tw: &tw,
+                                        colors: app_state.color_signal.0.get_untracked(),
                                     },
                                 )
                                 .unwrap();
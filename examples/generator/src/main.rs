@@ -6,14 +6,12 @@
 #![warn(clippy::perf)]
 #![warn(missing_debug_implementations)]
 #![warn(missing_docs)]
-extern crate colorous;
 extern crate rand;
 extern crate random_word;
 extern crate word_map;
 
 mod app;
 mod app_state;
-mod color_map;
 
 mod components;
 use app::*;
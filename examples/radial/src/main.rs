@@ -11,77 +11,32 @@
 
 extern crate word_map;
 
+use core::cmp::Reverse;
+
 use rand::distributions::{Distribution, WeightedIndex};
 use word_map::grid::Grid;
-use word_map::{block::Block, Orientation, Point2d};
+use word_map::layout::{LayoutOptions, RegionRule, Word};
+use word_map::scale::{ScaleMode, SizeScale};
+use word_map::svg::{self, RenderOptions};
+use word_map::Rect;
 
 static WIDTH: f32 = 800f32;
 static HEIGHT: f32 = 600f32;
-
-// A hightly skew distriubtion where small areas are
-// much more likely that large.
-fn render_block(b: &Block) {
-    // rec width is not text width.
-    let rec_width = b.top_right.x - b.bottom_left.x;
-    // rec_height is not text height.
-    let rec_height = b.bottom_left.y - b.top_right.y;
-
-    match b.orientation {
-        Orientation::Horizontal => {
-            println!(
-                "<text transform=\"translate({}, {}) rotate(0)\" font-size=\"{}\">{}</text>",
-                b.bottom_left.x, b.bottom_left.y, rec_height, b.text
-            );
-        }
-        Orientation::Vertical90 => {
-            // origin is top left
-            let top_left = Point2d {
-                x: b.top_right.x - rec_width,
-                y: b.top_right.y,
-            };
-            println!(
-                "<text transform=\"translate({}, {}) rotate(90)\" font-size=\"{}\">{}</text>",
-                top_left.x, top_left.y, rec_width, b.text
-            );
-        }
-        Orientation::Vertical270 => {
-            // origin is bottom right
-            let bottom_right = Point2d {
-                x: b.bottom_left.x + rec_width,
-                y: b.bottom_left.y,
-            };
-            println!(
-                "<text transform=\"translate({}, {}) rotate(270)\" font-size=\"{}\">{}</text>",
-                bottom_right.x, bottom_right.y, rec_width, b.text
-            );
-        }
-    }
-}
+// Number of words placed in the central region.
+static N_BIG: usize = 40;
 
 fn main() {
     use random_word::Lang;
 
-    // Heavily skew towards small areas.
-    static AREA_WEIGHTS: [usize; 9] = [100, 50, 1, 1, 1, 1, 1, 1, 1];
+    // Heavily skew towards small weights.
+    static WEIGHTS: [usize; 9] = [100, 50, 1, 1, 1, 1, 1, 1, 1];
+    static WEIGHT_VALUES: [u32; 9] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
 
     let mut rng = rand::thread_rng();
 
     let mut grid = Grid::new(WIDTH, HEIGHT);
 
-    // Parabolic area distribution. 1..81
-    let area_values: [f32; 9] = [
-        1_f32.powi(2),
-        2_f32.powi(2),
-        3_f32.powi(2),
-        4_f32.powi(2),
-        5_f32.powi(2),
-        6_f32.powi(2),
-        7_f32.powi(2),
-        8_f32.powi(2),
-        9_f32.powi(2),
-    ];
-
-    let dist = WeightedIndex::new(AREA_WEIGHTS).unwrap();
+    let dist = WeightedIndex::new(WEIGHTS).unwrap();
 
     println!("<?xml version=\"1.0\" standalone=\"no\"?><!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">
     <svg version=\"1.1\"
@@ -133,46 +88,55 @@ fn main() {
     println!("<rect id=\"background\" x=\"0\" y=\"0\" width=\"{WIDTH}\" height=\"{HEIGHT}\" fill=\"url(#RadialBackround)\"/>" );
     println!("<g font-family=\"Courier\">");
 
-    // Assign a random number to a word selected at random
-    let mut word_list = (0..200)
+    // Assign a random weight to a word selected at random
+    let words = (0..200)
         .map(|_| {
-            // input range 1..10 ( no zero width )
+            // input range 1..=9 ( no zero width )
             // based on a highly skewed algorithm.
-            //
-            // assuming a char width of 24px
-            // maps to a screen area based on 24x24 squares
-            let area = 7_f32 * 24_f32 * area_values[dist.sample(&mut rng)];
+            let weight = WEIGHT_VALUES[dist.sample(&mut rng)];
             let text = random_word::gen(Lang::En).to_uppercase();
-            (text, area)
+            Word::new(text, weight)
         })
         .collect::<Vec<_>>();
-    word_list.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-
-    // bounding rectangle fit the top 5% into 1/9 of the surface area.
-    let n_big = 40;
-
-    let sorted_iter = word_list.iter().rev();
-
-    // Limit the grid bounding rectangle to a small central region.
-    grid.bounding_rectangle_set(0.15 * WIDTH, 0.85 * WIDTH, 0.15 * HEIGHT, 0.85 * HEIGHT);
 
-    // Bigests first.
-    for (text, area) in sorted_iter.clone().take(n_big) {
-        grid.place_block(text, *area);
-    }
-
-    // Open up placement to the full surface.
-    grid.bounding_rectangle_clear();
-
-    for (text, area) in sorted_iter.skip(n_big) {
-        grid.place_block(text, *area);
-    }
-
-    for b in grid.blocks {
-        render_block(&b);
+    // Fit the largest fifth of the words into a small central region.
+    grid.region_add(
+        "centre",
+        Rect::new(0.15 * WIDTH, 0.85 * WIDTH, 0.15 * HEIGHT, 0.85 * HEIGHT),
+    )
+    .expect("centre region lies on the canvas");
+
+    let mut by_weight = words.iter().collect::<Vec<_>>();
+    by_weight.sort_by_key(|word| Reverse(word.weight));
+    let regions = by_weight
+        .iter()
+        .take(N_BIG)
+        .map(|word| RegionRule::Word {
+            text: word.text.clone(),
+            region: String::from("centre"),
+        })
+        .collect();
+
+    // Parabolic area distribution, font size grows linearly with weight.
+    let options = LayoutOptions {
+        size_scale: SizeScale::new(ScaleMode::Linear)
+            .domain_set([1_f32, 9_f32])
+            .font_size_range_set([7_f32, 61_f32]),
+        regions,
+        ..LayoutOptions::default()
+    };
+
+    // Biggest first, small words are free to use the full surface.
+    grid.layout(&words, &options);
+
+    // Close the gaps left by the random placement.
+    let center = grid.canvas().center();
+    grid.compact(&center, 10);
+
+    let render_options = RenderOptions::default();
+    for b in &grid.blocks {
+        println!("{}", svg::block(b, &render_options));
     }
     println!("</g>");
     println!("</svg>");
-
-    // let word = random_word::gen(Lang::En);
 }
@@ -1,9 +1,8 @@
+use crate::style::Style;
 use crate::Orientation;
 use crate::Point2d;
-use leptos::view;
-use leptos::IntoView;
-use leptos::View;
-use rand::rngs::ThreadRng;
+use crate::Rect;
+use rand::Rng;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -18,23 +17,61 @@ pub struct Block {
     pub bottom_left: Point2d,
     /// Orientation for the text.
     pub orientation: Orientation,
+    /// Font-size, the height of the text before rotation.
+    pub font_size: f32,
+    /// Weight of the word, zero when placed without one.
+    #[serde(default)]
+    pub weight: u32,
+    /// Presentation, given with the word.
+    #[serde(default)]
+    pub style: Option<Style>,
+    /// Link followed when the text is clicked.
+    #[serde(default)]
+    pub href: Option<String>,
+    /// Tooltip.
+    #[serde(default)]
+    pub title: Option<String>,
 }
 
 /// Blocks - a collection of `Bslock`
 #[derive(Clone, Debug, Deserialize, Default, Serialize, PartialEq)]
 pub struct Blocks(pub Vec<Block>);
 
+/// Returns the smallest rectangle holding every block, `None` when there
+/// are none.
+#[must_use]
+pub fn content_bounds(blocks: &[Block]) -> Option<Rect> {
+    blocks.iter().map(Block::bounds).reduce(|a, b| a.union(&b))
+}
+
 // converts the height of a character to its width
 //
 // TIMES NEW ROMAN
 static W_TO_H_RATIO: f32 = 50_f32 / 83_f32;
 
+// Height of capitals and ascenders above the baseline as a fraction of the
+// font-size.
+//
+// TIMES NEW ROMAN
+static ASCENT: f32 = 0.683_f32;
+
+// Depth of descenders below the baseline as a fraction of the font-size.
+//
+// TIMES NEW ROMAN
+static DESCENT: f32 = 0.217_f32;
+
+// Height of lowercase letters without ascenders above the baseline as a
+// fraction of the font-size.
+//
+// TIMES NEW ROMAN
+static X_HEIGHT: f32 = 0.448_f32;
+
 impl Block {
-    pub(crate) fn new_randomize_orientation(
+    pub(crate) fn new_randomize_orientation<R: Rng + ?Sized>(
         text: String,
         area: f32,
         origin: &Point2d,
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Self {
         let orientation = Orientation::at_random(rng);
         Self::new(text, area, origin, orientation)
@@ -94,123 +131,179 @@ impl Block {
             top_right,
             bottom_left,
             orientation,
+            font_size: text_height,
+            weight: 0,
+            style: None,
+            href: None,
+            title: None,
         }
     }
 
     // Compute the height/font-size given area and the number of characters.
-    fn h(area: f32, n_chars: f32) -> f32 {
+    pub(crate) fn h(area: f32, n_chars: f32) -> f32 {
         let h2 = area / (W_TO_H_RATIO * n_chars);
         h2.sqrt()
     }
 
-    fn is_inside(&self, point: &Point2d) -> bool {
-        self.bottom_left.x < point.x
-            && self.top_right.x > point.x
-            && self.bottom_left.y > point.y
-            && self.top_right.y < point.y
+    // Compute the area given the height/font-size and the number of characters.
+    pub(crate) fn area(h: f32, n_chars: f32) -> f32 {
+        W_TO_H_RATIO * n_chars * h * h
     }
 
-    // Test the candidate block against the object under test.
-    // Look North, East, South, West for intersection.
-    pub(crate) fn is_overlapping(&self, b: &Self) -> bool {
-        // if B is always above self.
-        if self.top_right.y > b.bottom_left.y {
-            return false;
-        }
+    /// Returns the bounding box.
+    #[must_use]
+    pub const fn bounds(&self) -> Rect {
+        Rect::new(
+            self.bottom_left.x,
+            self.top_right.x,
+            self.top_right.y,
+            self.bottom_left.y,
+        )
+    }
 
-        // if B is always right of self.
-        if self.top_right.x < b.bottom_left.x {
-            return false;
-        }
+    /// Returns the height of capitals and ascenders above the baseline.
+    #[must_use]
+    pub fn ascent(&self) -> f32 {
+        ASCENT * self.font_size
+    }
 
-        // if B is always below self.
-        if self.bottom_left.y < b.top_right.y {
-            return false;
-        }
+    /// Returns the depth of descenders below the baseline.
+    ///
+    /// Before rotation, the baseline lies this far above the bottom of the
+    /// box, so descenders stay inside it.
+    #[must_use]
+    pub fn descent(&self) -> f32 {
+        DESCENT * self.font_size
+    }
 
-        // If b is always left of self.
-        if self.bottom_left.x > b.top_right.x {
-            return false;
+    /// Returns the start of the baseline, where the text is anchored before
+    /// rotation.
+    #[must_use]
+    pub fn baseline_origin(&self) -> Point2d {
+        let bounds = self.bounds();
+        let descent = self.descent();
+        match self.orientation {
+            Orientation::Horizontal => Point2d {
+                x: bounds.xmin,
+                y: bounds.ymax - descent,
+            },
+            // Text runs downwards, the tops of the glyphs face right.
+            Orientation::Vertical90 => Point2d {
+                x: bounds.xmin + descent,
+                y: bounds.ymin,
+            },
+            // Text runs upwards, the tops of the glyphs face left.
+            Orientation::Vertical270 => Point2d {
+                x: bounds.xmax - descent,
+                y: bounds.ymax,
+            },
         }
-
-        true
     }
-}
 
-impl IntoView for Block {
-    /// Returns a series of SVG elements
-    ///
-    ///```text
-    /// view!{
-    /// <For each=move || blocks.get() key=|block| { block.text.clone() } let:b>
-    /// {b.into_view()}
-    /// </For>
-    /// </svg>
-    ///}
-    ///}}
-    /// ```
-    fn into_view(self) -> View {
-        // rec width is not text width.
-        let rec_width = self.top_right.x - self.bottom_left.x;
-        // rec_height is not text height.
-        let rec_height = self.bottom_left.y - self.top_right.y;
-
-        // top left
-        let rect_x = self.bottom_left.x;
-        let rect_y = self.bottom_left.y - rec_height;
-
-        let text = match self.orientation {
-            Orientation::Horizontal => {
-                let t = format!(
-                    "translate({},{}) rotate(0)",
-                    self.bottom_left.x, self.bottom_left.y
-                );
-                view! {
-                    <text transform=t font-size=rec_height>
-                        {self.text}
-                    </text>
-                }
-            }
-            Orientation::Vertical90 => {
-                // origin is top left
-                let top_left = Point2d {
-                    x: self.top_right.x - rec_width,
-                    y: self.top_right.y,
-                };
-                let t = format!("translate({},{}) rotate(90)", top_left.x, top_left.y);
-                view! {
-                    <text transform=t font_size=rec_width>
-                        {self.text}
-                    </text>
+    // Approximate glyph coverage.
+    //
+    // One rectangle per run of characters with the same extent, lowercase
+    // letters without ascenders leave the top of the block free, and only
+    // descenders reach below the baseline.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn glyph_rects(&self) -> Vec<Rect> {
+        let bounds = self.bounds();
+        let font_size = self.font_size;
+        // Length along the text.
+        let length = match self.orientation {
+            Orientation::Horizontal => bounds.width(),
+            Orientation::Vertical90 | Orientation::Vertical270 => bounds.height(),
+        };
+        let advance = length / self.text.chars().count().max(1) as f32;
+
+        // Distance of the baseline from the top.
+        let baseline = font_size - self.descent();
+
+        // Runs of (first char, last char + 1, distance of their top and bottom
+        // from the top of the block).
+        let mut runs: Vec<(usize, usize, f32, f32)> = vec![];
+        for (i, c) in self.text.chars().enumerate() {
+            let top = if c.is_whitespace() {
+                continue;
+            } else if "acemnorsuvwxzgpqy".contains(c) {
+                X_HEIGHT.mul_add(-font_size, baseline)
+            } else {
+                ASCENT.mul_add(-font_size, baseline)
+            };
+            let bottom = if "gjpqy".contains(c) {
+                font_size
+            } else {
+                baseline
+            };
+            match runs.last_mut() {
+                Some(run)
+                    if run.1 == i
+                        && (run.2 - top).abs() < f32::EPSILON
+                        && (run.3 - bottom).abs() < f32::EPSILON =>
+                {
+                    run.1 = i + 1;
                 }
+                _ => runs.push((i, i + 1, top, bottom)),
             }
-            Orientation::Vertical270 => {
-                // origin is bottom right
-                let bottom_right = Point2d {
-                    x: self.bottom_left.x + rec_width,
-                    y: self.bottom_left.y,
-                };
-                let t = format!(
-                    "translate({},{}) rotate(270)",
-                    bottom_right.x, bottom_right.y
-                );
-                view! {
-                    <text transform=t font-size=rec_width>
-                        {self.text}
-                    </text>
+        }
+
+        // Map text coordinates, u along the text and v down from the top
+        // of the glyphs, onto the canvas.
+        runs.into_iter()
+            .map(|(start, end, top, bottom)| {
+                let u0 = start as f32 * advance;
+                let u1 = end as f32 * advance;
+                let (v0, v1) = (top, bottom);
+                match self.orientation {
+                    Orientation::Horizontal => Rect::new(
+                        bounds.xmin + u0,
+                        bounds.xmin + u1,
+                        bounds.ymin + v0,
+                        bounds.ymin + v1,
+                    ),
+                    Orientation::Vertical90 => Rect::new(
+                        bounds.xmax - v1,
+                        bounds.xmax - v0,
+                        bounds.ymin + u0,
+                        bounds.ymin + u1,
+                    ),
+                    Orientation::Vertical270 => Rect::new(
+                        bounds.xmin + v0,
+                        bounds.xmin + v1,
+                        bounds.ymax - u1,
+                        bounds.ymax - u0,
+                    ),
                 }
-            }
-        };
+            })
+            .collect()
+    }
 
-        view! {
-            <rect x=rect_x y=rect_y width=rec_width height=rec_height></rect>
-            <circle class="bl" cx=self.bottom_left.x cy=self.bottom_left.y r="2"></circle>
-            <circle class="tr" cx=self.top_right.x cy=self.top_right.y r="2"></circle>
-            {text}
+    // A copy of the block scaled by `s`, then moved by the offset.
+    pub(crate) fn scale(&self, s: f32, offset: &Point2d) -> Self {
+        Self {
+            top_right: self.top_right.stretch(s, s, offset),
+            bottom_left: self.bottom_left.stretch(s, s, offset),
+            font_size: self.font_size * s,
+            ..self.clone()
+        }
+    }
+
+    // A copy of the block moved by (dx, dy).
+    pub(crate) fn translate(&self, dx: f32, dy: f32) -> Self {
+        Self {
+            top_right: Point2d {
+                x: self.top_right.x + dx,
+                y: self.top_right.y + dy,
+            },
+            bottom_left: Point2d {
+                x: self.bottom_left.x + dx,
+                y: self.bottom_left.y + dy,
+            },
+            ..self.clone()
         }
-        .into()
     }
 }
+
 // let a = vec![
 //   (String::from("TAXI"), 24. * 24. * 6.),
 //   // All A's have the same area per char.
@@ -282,11 +375,42 @@ mod test {
                 y: 200_f32,
             },
             orientation: Orientation::Horizontal,
+            font_size: 100_f32,
+            weight: 0,
+            style: None,
+            href: None,
+            title: None,
         };
 
         for (p, expected) in &VALUES {
-            println!("go");
-            assert_eq!(block.is_inside(p), *expected);
+            assert_eq!(block.bounds().contains(p), *expected);
         }
     }
+
+    #[test]
+    fn descenders_stay_inside() {
+        let origin = Point2d {
+            x: 100_f32,
+            y: 200_f32,
+        };
+        let block = Block::new(
+            String::from("Tag"),
+            3000_f32,
+            &origin,
+            Orientation::Horizontal,
+        );
+        let bounds = block.bounds();
+        let baseline = block.baseline_origin();
+        assert!((baseline.y - (bounds.ymax - block.descent())).abs() < 1e-3);
+        assert!(block.ascent() + block.descent() < block.font_size);
+
+        // "T" and "a" sit on the baseline, "g" reaches the bottom of the box.
+        let glyphs = block.glyph_rects();
+        assert_eq!(glyphs.len(), 3);
+        assert!(glyphs.iter().all(|glyph| bounds.contains_rect(glyph)));
+        assert!((glyphs[0].ymax - baseline.y).abs() < 1e-3);
+        assert!((glyphs[1].ymax - baseline.y).abs() < 1e-3);
+        assert!((glyphs[2].ymax - bounds.ymax).abs() < 1e-3);
+        assert!(glyphs[0].ymin < glyphs[1].ymin);
+    }
 }
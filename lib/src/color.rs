@@ -0,0 +1,359 @@
+use core::fmt::{self, Debug, Formatter};
+
+use colorous::{Color, Gradient};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::block::Block;
+use crate::Orientation;
+
+/// The gradients and palettes, such as `colorous::VIRIDIS` and
+/// `colorous::CATEGORY10`.
+pub use colorous;
+
+/// A source of colours.
+#[derive(Clone, Copy)]
+pub enum Scheme {
+    /// A continuous gradient, such as `colorous::PLASMA`.
+    Gradient(Gradient),
+    /// Distinct colours, such as `colorous::CATEGORY10`.
+    Palette(&'static [Color]),
+}
+
+impl Debug for Scheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gradient(_) => f.write_str("Gradient"),
+            Self::Palette(colors) => f.debug_tuple("Palette").field(colors).finish(),
+        }
+    }
+}
+
+impl Scheme {
+    /// Returns the colour at `t`, between 0 and 1.
+    ///
+    /// A palette is divided into bands of equal width.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn eval(&self, t: f64) -> Color {
+        // Written to also map NaN to 0.
+        let t = if t > 0_f64 { t.min(1_f64) } else { 0_f64 };
+        match self {
+            Self::Gradient(gradient) => gradient.eval_continuous(t),
+            Self::Palette(colors) => {
+                let i = (t * colors.len() as f64) as usize;
+                colors
+                    .get(i.min(colors.len().saturating_sub(1)))
+                    .copied()
+                    .unwrap_or(BLACK)
+            }
+        }
+    }
+
+    /// Returns the colour of category `i` out of `n`.
+    ///
+    /// A palette repeats when there are more categories than colours, a
+    /// gradient is sampled at evenly spaced points.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn category(&self, i: usize, n: usize) -> Color {
+        match self {
+            Self::Gradient(gradient) => {
+                if n > 1 {
+                    gradient.eval_continuous(i as f64 / (n - 1) as f64)
+                } else {
+                    gradient.eval_continuous(0.5_f64)
+                }
+            }
+            Self::Palette(colors) => {
+                if colors.is_empty() {
+                    BLACK
+                } else {
+                    colors[i % colors.len()]
+                }
+            }
+        }
+    }
+}
+
+/// The property of a block that selects its colour.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorBy {
+    /// The weight of the word.
+    #[default]
+    Weight,
+    /// The area of the block.
+    Area,
+    /// The position of the block when sorted by area, largest first.
+    Rank,
+    /// Each orientation has its own colour.
+    Orientation,
+    /// A colour at random, repeatable for a given seed.
+    Random,
+}
+
+/// Assigns colours to blocks.
+///
+/// ```text
+/// let color_map = ColorMap::new(Scheme::Gradient(colorous::VIRIDIS), ColorBy::Weight);
+/// color_map.apply(&mut grid.blocks);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ColorMap {
+    scheme: Scheme,
+    by: ColorBy,
+    // Values mapped onto the ends of the scheme.
+    domain: Option<[f64; 2]>,
+    seed: u64,
+}
+
+impl ColorMap {
+    /// Returns a map given the colours and the property selecting them.
+    #[must_use]
+    pub const fn new(scheme: Scheme, by: ColorBy) -> Self {
+        Self {
+            scheme,
+            by,
+            domain: None,
+            seed: 0,
+        }
+    }
+
+    /// Returns the source of colours.
+    #[must_use]
+    pub const fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    /// Returns the property selecting the colour.
+    #[must_use]
+    pub const fn by(&self) -> ColorBy {
+        self.by
+    }
+
+    /// Sets the weights, or areas, mapped onto the ends of the scheme.
+    ///
+    /// By default the smallest and largest values of the blocks are used.
+    #[must_use]
+    pub const fn domain_set(mut self, domain: [f64; 2]) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    /// Sets the seed used by `ColorBy::Random`.
+    #[must_use]
+    pub const fn seed_set(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns the colour of a value in the domain.
+    ///
+    /// Without a domain, values between 0 and 1 cover the scheme.
+    #[must_use]
+    pub fn value(&self, x: f64) -> Color {
+        let [min, max] = self.domain.unwrap_or([0_f64, 1_f64]);
+        self.scheme.eval(normalize(x, min, max))
+    }
+
+    /// Returns the colour of each block, in order.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn colors(&self, blocks: &[Block]) -> Vec<Color> {
+        match self.by {
+            ColorBy::Weight => self.by_value(blocks, |block| f64::from(block.weight)),
+            ColorBy::Area => self.by_value(blocks, |block| f64::from(block.bounds().area())),
+            ColorBy::Rank => {
+                let mut order = (0..blocks.len()).collect::<Vec<_>>();
+                order.sort_by(|a, b| {
+                    let area_a = blocks[*a].bounds().area();
+                    let area_b = blocks[*b].bounds().area();
+                    area_b.total_cmp(&area_a)
+                });
+                let mut colors = vec![BLACK; blocks.len()];
+                for (rank, i) in order.into_iter().enumerate() {
+                    colors[i] = self.scheme.category(rank, blocks.len());
+                }
+                colors
+            }
+            ColorBy::Orientation => blocks
+                .iter()
+                .map(|block| {
+                    let i = match block.orientation {
+                        Orientation::Horizontal => 0,
+                        Orientation::Vertical90 => 1,
+                        Orientation::Vertical270 => 2,
+                    };
+                    self.scheme.category(i, 3)
+                })
+                .collect(),
+            ColorBy::Random => {
+                let mut rng = StdRng::seed_from_u64(self.seed);
+                blocks
+                    .iter()
+                    .map(|_| self.scheme.eval(rng.gen::<f64>()))
+                    .collect()
+            }
+        }
+    }
+
+    /// Sets the fill of each block that does not have one.
+    pub fn apply(&self, blocks: &mut [Block]) {
+        let colors = self.colors(blocks);
+        for (block, color) in blocks.iter_mut().zip(colors) {
+            let style = block.style.get_or_insert_with(Default::default);
+            if style.fill.is_none() {
+                style.fill = Some(hex(color));
+            }
+        }
+    }
+
+    // Maps a property onto the scheme, using the domain when set.
+    fn by_value<F>(&self, blocks: &[Block], property: F) -> Vec<Color>
+    where
+        F: Fn(&Block) -> f64,
+    {
+        let values = blocks.iter().map(property).collect::<Vec<_>>();
+        let [min, max] = self.domain.unwrap_or_else(|| {
+            values
+                .iter()
+                .fold([f64::INFINITY, f64::NEG_INFINITY], |[min, max], v| {
+                    [min.min(*v), max.max(*v)]
+                })
+        });
+        values
+            .into_iter()
+            .map(|v| self.scheme.eval(normalize(v, min, max)))
+            .collect()
+    }
+}
+
+/// Returns the colour in the form "#rrggbb".
+#[must_use]
+pub fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// Parses a colour in the form "#rgb" or "#rrggbb".
+#[must_use]
+pub fn parse_hex(input: &str) -> Option<Color> {
+    let digits = input.strip_prefix('#')?;
+    if !digits.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&digits[i * len..(i + 1) * len], 16);
+    let color = match digits.len() {
+        3 => Color {
+            r: channel(0, 1).ok()? * 17,
+            g: channel(1, 1).ok()? * 17,
+            b: channel(2, 1).ok()? * 17,
+        },
+        6 => Color {
+            r: channel(0, 2).ok()?,
+            g: channel(1, 2).ok()?,
+            b: channel(2, 2).ok()?,
+        },
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// Returns the WCAG contrast ratio of two colours, from 1 to 21.
+///
+/// WCAG level AA asks for 4.5 for body text, and 3 for large text.
+#[must_use]
+pub fn contrast_ratio(a: Color, b: Color) -> f64 {
+    let la = relative_luminance(a);
+    let lb = relative_luminance(b);
+    (la.max(lb) + 0.05_f64) / (la.min(lb) + 0.05_f64)
+}
+
+static BLACK: Color = Color { r: 0, g: 0, b: 0 };
+
+// Position of a value between the ends of the domain.
+//
+// The mid point when the domain is empty.
+fn normalize(x: f64, min: f64, max: f64) -> f64 {
+    if max > min {
+        (x - min) / (max - min)
+    } else {
+        0.5_f64
+    }
+}
+
+// As defined by WCAG 2.
+fn relative_luminance(color: Color) -> f64 {
+    let linear = |c: u8| {
+        let c = f64::from(c) / 255_f64;
+        if c <= 0.039_28_f64 {
+            c / 12.92_f64
+        } else {
+            ((c + 0.055_f64) / 1.055_f64).powf(2.4_f64)
+        }
+    };
+    0.0722_f64.mul_add(
+        linear(color.b),
+        0.2126_f64.mul_add(linear(color.r), 0.7152_f64 * linear(color.g)),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Point2d;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn block(weight: u32, orientation: Orientation) -> Block {
+        Block {
+            weight,
+            ..Block::new(
+                String::from("word"),
+                100_f32 * weight as f32,
+                &Point2d {
+                    x: 100_f32,
+                    y: 100_f32,
+                },
+                orientation,
+            )
+        }
+    }
+
+    #[test]
+    fn weight_spans_the_gradient() {
+        let blocks = vec![
+            block(1, Orientation::Horizontal),
+            block(5, Orientation::Horizontal),
+            block(9, Orientation::Vertical90),
+        ];
+        let color_map = ColorMap::new(Scheme::Gradient(colorous::VIRIDIS), ColorBy::Weight);
+        let colors = color_map.colors(&blocks);
+        let viridis = |t| hex(colorous::VIRIDIS.eval_continuous(t));
+        assert_eq!(hex(colors[0]), viridis(0_f64));
+        assert_eq!(hex(colors[1]), viridis(0.5_f64));
+        assert_eq!(hex(colors[2]), viridis(1_f64));
+
+        // Largest first.
+        let palette = Scheme::Palette(&colorous::CATEGORY10);
+        let colors = ColorMap::new(palette, ColorBy::Rank).colors(&blocks);
+        assert_eq!(hex(colors[2]), hex(colorous::CATEGORY10[0]));
+        assert_eq!(hex(colors[0]), hex(colorous::CATEGORY10[2]));
+
+        let colors = ColorMap::new(palette, ColorBy::Orientation).colors(&blocks);
+        assert_eq!(hex(colors[0]), hex(colors[1]));
+        assert_eq!(hex(colors[2]), hex(colorous::CATEGORY10[1]));
+    }
+
+    #[test]
+    fn contrast() {
+        let black = parse_hex("#000").expect("short form");
+        let white = parse_hex("#ffffff").expect("long form");
+        assert!((contrast_ratio(black, white) - 21_f64).abs() < 1e-9);
+        assert!((contrast_ratio(white, white) - 1_f64).abs() < 1e-9);
+        assert_eq!(hex(white), "#ffffff");
+        assert!(parse_hex("#12345").is_none());
+        assert!(parse_hex("123456").is_none());
+    }
+}
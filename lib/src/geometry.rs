@@ -0,0 +1,221 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Primitive  representation of a point on the canvas.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Point2d {
+    /// x coordinate
+    pub x: f32,
+    /// y coordinate
+    pub y: f32,
+}
+
+impl Point2d {
+    // The point with x scaled by sx and y by sy, then moved by the offset.
+    //
+    // Not const, as const mul_add is newer than the rust-version.
+    #[allow(clippy::missing_const_for_fn)]
+    pub(crate) fn stretch(&self, sx: f32, sy: f32, offset: &Self) -> Self {
+        Self {
+            x: self.x.mul_add(sx, offset.x),
+            y: self.y.mul_add(sy, offset.y),
+        }
+    }
+}
+
+/// An axis aligned rectangle on the canvas.
+///
+/// The y axis runs downwards, so `ymin` is the top edge.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Rect {
+    /// Left edge.
+    pub xmin: f32,
+    /// Right edge.
+    pub xmax: f32,
+    /// Top edge.
+    pub ymin: f32,
+    /// Bottom edge.
+    pub ymax: f32,
+}
+
+impl Rect {
+    /// Returns a rectangle given its edges.
+    #[must_use]
+    pub const fn new(xmin: f32, xmax: f32, ymin: f32, ymax: f32) -> Self {
+        Self {
+            xmin,
+            xmax,
+            ymin,
+            ymax,
+        }
+    }
+
+    /// Returns the distance between the left and right edges.
+    #[must_use]
+    pub fn width(&self) -> f32 {
+        self.xmax - self.xmin
+    }
+
+    /// Returns the distance between the top and bottom edges.
+    #[must_use]
+    pub fn height(&self) -> f32 {
+        self.ymax - self.ymin
+    }
+
+    /// Returns width * height.
+    #[must_use]
+    pub fn area(&self) -> f32 {
+        self.width() * self.height()
+    }
+
+    /// Returns the mid point.
+    #[must_use]
+    pub fn center(&self) -> Point2d {
+        Point2d {
+            x: self.xmin + self.width() / 2_f32,
+            y: self.ymin + self.height() / 2_f32,
+        }
+    }
+
+    /// Returns the corners, clockwise from the top left.
+    #[must_use]
+    pub const fn corners(&self) -> [Point2d; 4] {
+        [
+            Point2d {
+                x: self.xmin,
+                y: self.ymin,
+            },
+            Point2d {
+                x: self.xmax,
+                y: self.ymin,
+            },
+            Point2d {
+                x: self.xmax,
+                y: self.ymax,
+            },
+            Point2d {
+                x: self.xmin,
+                y: self.ymax,
+            },
+        ]
+    }
+
+    /// Is the point inside the rectangle or on its edge.
+    #[must_use]
+    pub fn contains(&self, p: &Point2d) -> bool {
+        p.x >= self.xmin && p.x <= self.xmax && p.y >= self.ymin && p.y <= self.ymax
+    }
+
+    /// Is the whole of the other rectangle inside this rectangle.
+    ///
+    /// Edges may touch.
+    #[must_use]
+    pub fn contains_rect(&self, other: &Self) -> bool {
+        other.xmin >= self.xmin
+            && other.xmax <= self.xmax
+            && other.ymin >= self.ymin
+            && other.ymax <= self.ymax
+    }
+
+    /// Do the two rectangles share any area.
+    ///
+    /// Rectangles that only touch along an edge do not intersect.
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.xmin < other.xmax
+            && self.xmax > other.xmin
+            && self.ymin < other.ymax
+            && self.ymax > other.ymin
+    }
+
+    /// Returns the area shared by the two rectangles, if any.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if self.intersects(other) {
+            Some(Self {
+                xmin: self.xmin.max(other.xmin),
+                xmax: self.xmax.min(other.xmax),
+                ymin: self.ymin.max(other.ymin),
+                ymax: self.ymax.min(other.ymax),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the smallest rectangle holding both rectangles.
+    #[must_use]
+    pub const fn union(&self, other: &Self) -> Self {
+        Self {
+            xmin: self.xmin.min(other.xmin),
+            xmax: self.xmax.max(other.xmax),
+            ymin: self.ymin.min(other.ymin),
+            ymax: self.ymax.max(other.ymax),
+        }
+    }
+
+    // The rectangle with x scaled by sx and y by sy, then moved by the offset.
+    //
+    // The scales must be positive.
+    #[allow(clippy::missing_const_for_fn)]
+    pub(crate) fn stretch(&self, sx: f32, sy: f32, offset: &Point2d) -> Self {
+        Self {
+            xmin: self.xmin.mul_add(sx, offset.x),
+            xmax: self.xmax.mul_add(sx, offset.x),
+            ymin: self.ymin.mul_add(sy, offset.y),
+            ymax: self.ymax.mul_add(sy, offset.y),
+        }
+    }
+
+    /// Returns the rectangle grown by `d` on every side.
+    ///
+    /// A negative value shrinks the rectangle.
+    #[must_use]
+    pub fn inflate(&self, d: f32) -> Self {
+        Self {
+            xmin: self.xmin - d,
+            xmax: self.xmax + d,
+            ymin: self.ymin - d,
+            ymax: self.ymax + d,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn measure() {
+        let r = Rect::new(10_f32, 50_f32, 20_f32, 40_f32);
+        assert!((r.width() - 40_f32).abs() < f32::EPSILON);
+        assert!((r.height() - 20_f32).abs() < f32::EPSILON);
+        assert!((r.area() - 800_f32).abs() < f32::EPSILON);
+        assert_eq!(
+            r.center(),
+            Point2d {
+                x: 30_f32,
+                y: 30_f32
+            }
+        );
+        assert_eq!(
+            r.corners()[2],
+            Point2d {
+                x: 50_f32,
+                y: 40_f32
+            }
+        );
+    }
+
+    #[test]
+    fn touching_edges() {
+        let a = Rect::new(0_f32, 10_f32, 0_f32, 10_f32);
+        let b = Rect::new(10_f32, 20_f32, 0_f32, 10_f32);
+        assert!(!a.intersects(&b));
+        assert_eq!(a.intersection(&b), None);
+        assert!(a.inflate(1_f32).intersects(&b));
+        assert!(a.union(&b).contains_rect(&a));
+        assert!(a.union(&b).contains_rect(&b));
+        assert_eq!(a.union(&b), Rect::new(0_f32, 20_f32, 0_f32, 10_f32));
+    }
+}
@@ -1,3 +1,11 @@
+#[cfg(all(test, feature = "parallel"))]
+use core::cell::Cell;
+use core::cmp::Reverse;
+use core::f32::consts::TAU;
+use core::fmt::{self, Display, Formatter};
+use std::error::Error;
+use std::time::Instant;
+
 use nom::character::complete::alpha1;
 use nom::character::complete::char;
 use nom::character::complete::digit1;
@@ -6,73 +14,310 @@ use nom::multi::separated_list1;
 use nom::sequence::separated_pair;
 use nom::IResult;
 
-use rand::{rngs::ThreadRng, Rng};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-use crate::{block::Block, Point2d};
+use crate::index::BlockIndex;
+use crate::layout::{CancelToken, LayoutOptions, LayoutResult, PlacementEvent, Word, MAX_ATTEMPTS};
+use crate::occupancy::Occupancy;
+use crate::stats::LayoutStats;
+use crate::{block, block::Block, shape::Shape, style::Style, Point2d, Rect};
 
 /// Parser only structure.
 #[derive(Debug, Eq, PartialEq)]
 pub struct TextWeight<'a>(pub &'a str, pub u32);
 
+/// Reasons for rejecting a placement rectangle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BoundsError {
+    /// The width or height is not positive.
+    Empty,
+    /// The rectangle extends beyond the edge of the canvas.
+    OutsideCanvas,
+}
+
+impl Display for BoundsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "rectangle has no area"),
+            Self::OutsideCanvas => write!(f, "rectangle extends beyond the canvas"),
+        }
+    }
+}
+
+impl Error for BoundsError {}
+
+/// The minimum gap kept between placed blocks.
+///
+/// Padding only affects collision checks, the geometry of a block is
+/// untouched.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Padding {
+    /// Blocks may touch.
+    #[default]
+    None,
+    /// A fixed gap in canvas units.
+    Absolute(f32),
+    /// A gap given as a fraction of the font size.
+    ///
+    /// Between two blocks the gap is the mean of the padding of each.
+    FontFraction(f32),
+}
+
+impl Padding {
+    // Each block is inflated by half the gap.
+    fn margin(self, block: &Block) -> f32 {
+        match self {
+            Self::None => 0_f32,
+            Self::Absolute(gap) => gap / 2_f32,
+            Self::FontFraction(fraction) => fraction * block.font_size / 2_f32,
+        }
+    }
+}
+
+/// How a candidate block is tested against the placed blocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Collision {
+    /// Blocks collide when their rectangles overlap.
+    #[default]
+    Rectangle,
+    /// Blocks collide when their glyphs overlap.
+    ///
+    /// The glyphs are rasterised onto a grid of square cells, `cell` is the
+    /// size of a cell in canvas units. Small words can nest in the space
+    /// above lowercase letters of large words, at the cost of speed.
+    Glyph {
+        /// Size of a cell in canvas units.
+        ///
+        /// A size that is not positive and finite is replaced by 1.
+        cell: f32,
+    },
+}
+
+// Number of candidates generated, and tested, together.
+const BATCH_SIZE: usize = 64;
+
+// Angle, in radians, between successive candidates on the spiral searched
+// by `relayout`.
+const SPIRAL_STEP: f32 = 0.5_f32;
+
+// The outcome of placing a single block.
+#[derive(Debug, PartialEq, Eq)]
+enum Placement {
+    Placed,
+    Dropped,
+    Interrupted,
+}
+
+// Conditions stopping a layout early.
+#[derive(Debug, Default)]
+struct Interrupt<'a> {
+    deadline: Option<Instant>,
+    cancel: Option<&'a CancelToken>,
+}
+
+impl Interrupt<'_> {
+    fn is_interrupted(&self) -> bool {
+        self.cancel.is_some_and(CancelToken::is_cancelled)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
 /// A collection of blocks.
 #[derive(Debug)]
 pub struct Grid {
-    rng: ThreadRng,
-    width: f32,
-    height: f32,
+    rng: StdRng,
+    canvas: Rect,
     /// A collection of blocks.
     pub blocks: Vec<Block>,
     // Bounding rectangle
-    xmin: f32,
-    xmax: f32,
-    ymin: f32,
-    ymax: f32,
+    bounds: Rect,
+    // Outline constraining placement.
+    shape: Option<Shape>,
+    // Named placement rectangles.
+    regions: Vec<(String, Rect)>,
+    // Rectangles no block may overlap.
+    exclusions: Vec<Rect>,
+    // Minimum gap between blocks.
+    padding: Padding,
+    collision: Collision,
+    // Cells covered by glyphs, when testing collisions at glyph level.
+    occupancy: Option<Occupancy>,
+    // Number of blocks that failed to be placed.
+    dropped: usize,
+    // Number of candidates generated for the placed blocks.
+    attempts: usize,
 }
 
 impl Grid {
     /// Returns a grid object given the dimension of the canvas/svg
     #[must_use]
     pub fn new(width: f32, height: f32) -> Self {
+        Self::new_with_rng(width, height, StdRng::from_entropy())
+    }
+
+    /// Returns a grid that lays out blocks identically for a given seed.
+    ///
+    /// The output is the same with or without the `parallel` feature.
+    #[must_use]
+    pub fn new_with_seed(width: f32, height: f32, seed: u64) -> Self {
+        Self::new_with_rng(width, height, StdRng::seed_from_u64(seed))
+    }
+
+    const fn new_with_rng(width: f32, height: f32, rng: StdRng) -> Self {
+        let canvas = Rect::new(0_f32, width, 0_f32, height);
         Self {
-            rng: rand::thread_rng(),
+            rng,
             blocks: vec![],
-            width,
-            height,
-            xmin: 0_f32,
-            xmax: width,
-            ymin: 0_f32,
-            ymax: height,
+            canvas,
+            bounds: canvas,
+            shape: None,
+            regions: vec![],
+            exclusions: vec![],
+            padding: Padding::None,
+            collision: Collision::Rectangle,
+            occupancy: None,
+            dropped: 0,
+            attempts: 0,
         }
     }
 
+    /// Returns the full surface.
+    #[must_use]
+    pub const fn canvas(&self) -> Rect {
+        self.canvas
+    }
+
     /// Returns the sub rectangle.
     #[must_use]
-    pub const fn bounding_rectangle(&self) -> (f32, f32, f32, f32) {
-        (self.xmin, self.xmax, self.ymin, self.ymax)
+    pub const fn bounding_rectangle(&self) -> Rect {
+        self.bounds
     }
 
     /// Reset placement to the full surface.
     pub fn bounding_rectangle_clear(&mut self) {
-        self.xmin = 0_f32;
-        self.xmax = self.width;
-        self.ymin = 0_f32;
-        self.ymax = self.height;
+        self.bounds = self.canvas;
     }
 
     /// Constrain the sub rectangle.
     ///
     /// All blocks with be placed inside the rectangle.
-    pub fn bounding_rectangle_set(&mut self, xmin: f32, xmax: f32, ymin: f32, ymax: f32) {
-        debug_assert!(xmin > 0_f32);
-        debug_assert!(ymin > 0_f32);
-        debug_assert!(xmax > xmin);
-        debug_assert!(ymax > ymin);
-        debug_assert!(self.width > xmax);
-        debug_assert!(self.height > ymax);
-        self.xmin = xmin;
-        self.xmax = xmax;
-        self.ymin = ymin;
-        self.ymax = ymax;
+    ///
+    /// # Panics
+    ///
+    /// When the rectangle is rejected by `try_bounding_rectangle_set`.
+    pub fn bounding_rectangle_set(&mut self, rect: Rect) {
+        if let Err(e) = self.try_bounding_rectangle_set(rect) {
+            panic!("invalid bounding rectangle {rect:?}: {e}");
+        }
+    }
+
+    /// Constrain the sub rectangle.
+    ///
+    /// All blocks with be placed inside the rectangle.
+    ///
+    /// # Errors
+    ///
+    /// When the rectangle has no area or is not on the canvas. The
+    /// rectangle may touch the edge of the canvas.
+    pub fn try_bounding_rectangle_set(&mut self, rect: Rect) -> Result<(), BoundsError> {
+        self.validate(&rect)?;
+        self.bounds = rect;
+        Ok(())
+    }
+
+    /// Returns the outline constraining placement.
+    #[must_use]
+    pub const fn shape(&self) -> Option<&Shape> {
+        self.shape.as_ref()
+    }
+
+    /// Constrain placement to an outline.
+    ///
+    /// Blocks must lie wholly inside both the shape and the bounding rectangle.
+    pub fn shape_set(&mut self, shape: Shape) {
+        self.shape = Some(shape);
+    }
+
+    /// Remove the outline constraining placement.
+    pub fn shape_clear(&mut self) {
+        self.shape = None;
+    }
+
+    /// Returns the rectangle of a named region.
+    #[must_use]
+    pub fn region(&self, name: &str) -> Option<Rect> {
+        self.regions
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, rect)| *rect)
+    }
+
+    /// Define a named placement region, replacing any region of the same name.
+    ///
+    /// See `place_block_in`.
+    ///
+    /// # Errors
+    ///
+    /// When the rectangle has no area or is not on the canvas.
+    pub fn region_add(&mut self, name: &str, rect: Rect) -> Result<(), BoundsError> {
+        self.validate(&rect)?;
+        self.region_remove(name);
+        self.regions.push((name.to_string(), rect));
+        Ok(())
+    }
+
+    /// Remove a named placement region.
+    pub fn region_remove(&mut self, name: &str) {
+        self.regions.retain(|(n, _)| n != name);
+    }
+
+    /// Returns the rectangles that no block may overlap.
+    #[must_use]
+    pub fn exclusions(&self) -> &[Rect] {
+        &self.exclusions
+    }
+
+    /// Reserve a rectangle, a title strip or logo box for example.
+    ///
+    /// No block, in any region, will be placed over it.
+    pub fn exclusion_add(&mut self, rect: Rect) {
+        self.exclusions.push(rect);
+    }
+
+    /// Remove all exclusion zones.
+    pub fn exclusions_clear(&mut self) {
+        self.exclusions.clear();
+    }
+
+    /// Returns the minimum gap kept between blocks.
+    #[must_use]
+    pub const fn padding(&self) -> Padding {
+        self.padding
+    }
+
+    /// Set the minimum gap kept between blocks placed from now on.
+    pub fn padding_set(&mut self, padding: Padding) {
+        self.padding = padding;
+        self.occupancy_rebuild();
+    }
+
+    /// Returns how candidate blocks are tested against placed blocks.
+    #[must_use]
+    pub const fn collision(&self) -> Collision {
+        self.collision
+    }
+
+    /// Set how candidate blocks are tested against placed blocks.
+    ///
+    /// Blocks pushed directly onto `blocks` are only seen by glyph level
+    /// collision checks after this is called again.
+    pub fn collision_set(&mut self, collision: Collision) {
+        self.collision = collision;
+        self.occupancy_rebuild();
     }
 
     /// Generate candidate blocks and fit them into the bounding rectangle.
@@ -80,22 +325,317 @@ impl Grid {
     /// WARNING:
     /// O(n^2) operation
     pub fn place_block(&mut self, text: &str, area: f32) -> bool {
-        // Give a block 2000 attempts to get placed.
-        // 2 orientations
-        for _ in 0..2000 {
-            let origin = self.point_at_random();
-            let block =
-                Block::new_randomize_orientation(text.to_string(), area, &origin, &mut self.rng);
-            // Block must be inside the bounding rectangle.
-            if self.is_inside(&block.bottom_left)
-                && self.is_inside(&block.top_right)
-                && !self.is_any_block_overlapping(&block)
-            {
-                self.blocks.push(block);
-                return true;
+        self.place_block_within(text, area, self.bounds, MAX_ATTEMPTS, &Interrupt::default())
+            == Placement::Placed
+    }
+
+    /// As `place_block`, the style is attached to the new block.
+    pub fn place_block_with_style(&mut self, text: &str, area: f32, style: Style) -> bool {
+        let placed = self.place_block(text, area);
+        if let (true, Some(block)) = (placed, self.blocks.last_mut()) {
+            block.style = Some(style);
+        }
+        placed
+    }
+
+    /// Generate candidate blocks and fit them into a named region.
+    ///
+    /// The bounding rectangle is ignored. Returns false if the region is
+    /// unknown.
+    pub fn place_block_in(&mut self, text: &str, area: f32, region: &str) -> bool {
+        self.place_block_in_with(text, area, region, MAX_ATTEMPTS, &Interrupt::default())
+            == Placement::Placed
+    }
+
+    /// Place a list of words, largest weight first.
+    ///
+    /// The layout stops early, leaving the words placed so far, when the
+    /// time budget runs out or the layout is cancelled.
+    ///
+    /// (text, weight) pairs convert into unstyled words.
+    ///
+    /// ```text
+    /// let words = pairs.iter().map(Word::from).collect::<Vec<_>>();
+    /// ```
+    pub fn layout(&mut self, words: &[Word], options: &LayoutOptions) -> LayoutResult {
+        self.layout_with_observer(words, options, |_| {})
+    }
+
+    /// As `layout`, reporting each word to the observer as it is processed.
+    ///
+    /// Frontends can use the events to animate words appearing, or to
+    /// drive a progress bar.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn layout_with_observer<F>(
+        &mut self,
+        words: &[Word],
+        options: &LayoutOptions,
+        mut observer: F,
+    ) -> LayoutResult
+    where
+        F: FnMut(&PlacementEvent),
+    {
+        let mut sorted = words.iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|word| Reverse(word.weight));
+
+        let interrupt = Interrupt {
+            deadline: options.time_budget.map(|budget| Instant::now() + budget),
+            cancel: options.cancel.as_ref(),
+        };
+
+        let total = sorted.len();
+        let mut result = LayoutResult {
+            complete: true,
+            ..LayoutResult::default()
+        };
+        for (i, word) in sorted.into_iter().enumerate() {
+            let text = word.text.as_str();
+            let area = options.size_scale.area(text, word.weight);
+            let placement = match options.region_for(word) {
+                Some(region) => {
+                    self.place_block_in_with(text, area, region, options.max_attempts, &interrupt)
+                }
+                None => self.place_block_within(
+                    text,
+                    area,
+                    self.bounds,
+                    options.max_attempts,
+                    &interrupt,
+                ),
+            };
+            let progress = (i + 1) as f32 / total as f32;
+            match placement {
+                Placement::Placed => {
+                    result.placed += 1;
+                    if let Some(block) = self.blocks.last_mut() {
+                        block.weight = word.weight;
+                        block.style.clone_from(&word.style);
+                        block.href.clone_from(&word.href);
+                        block.title.clone_from(&word.title);
+                        observer(&PlacementEvent::Placed { block, progress });
+                    }
+                }
+                Placement::Dropped => {
+                    result.dropped += 1;
+                    observer(&PlacementEvent::Dropped { text, progress });
+                }
+                Placement::Interrupted => {
+                    result.complete = false;
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    /// Slide the blocks toward the target until they would collide.
+    ///
+    /// Blocks are moved largest first, the set of placed words is unchanged.
+    /// A block may leave the bounding rectangle or its region, but stays on
+    /// the canvas, inside the shape and clear of the exclusion zones.
+    /// Passes are repeated until no block moves or `max_passes` is reached.
+    ///
+    /// To pull words into the middle of a region, use the center of the
+    /// region as the target.
+    ///
+    /// Returns the number of passes made.
+    pub fn compact(&mut self, target: &Point2d, max_passes: usize) -> usize {
+        // Moves smaller than this are ignored.
+        static MIN_STEP: f32 = 0.5_f32;
+
+        // Count any blocks pushed directly onto `blocks`, so taking them out
+        // below releases only what they hold.
+        self.occupancy_rebuild();
+
+        let mut order = (0..self.blocks.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let area_a = self.blocks[*a].bounds().area();
+            let area_b = self.blocks[*b].bounds().area();
+            area_b.total_cmp(&area_a)
+        });
+
+        for pass in 0..max_passes {
+            let mut moved_any = false;
+            for &i in &order {
+                // Take the block out, so it cannot collide with itself.
+                let block = self.remove_block(i);
+                let center = block.bounds().center();
+                let dx = target.x - center.x;
+                let dy = target.y - center.y;
+                let distance = dx.hypot(dy);
+
+                // Steps no longer than the block is thick, so the block
+                // slides rather than jumps over its neighbours.
+                let bounds = block.bounds();
+                let mut step = distance.min(bounds.width().min(bounds.height()));
+                let mut moved = 0_f32;
+                loop {
+                    if step < MIN_STEP {
+                        break;
+                    }
+                    let next = (moved + step).min(distance);
+                    let t = next / distance;
+                    if next > moved && self.is_valid_move(&block.translate(t * dx, t * dy)) {
+                        moved = next;
+                    } else {
+                        step /= 2_f32;
+                    }
+                }
+
+                let block = if moved > 0_f32 {
+                    moved_any = true;
+                    let t = moved / distance;
+                    block.translate(t * dx, t * dy)
+                } else {
+                    block
+                };
+                self.insert_block(i, block);
+            }
+            if !moved_any {
+                return pass + 1;
+            }
+        }
+        max_passes
+    }
+
+    /// Returns a grid of another size holding the same layout, scaled
+    /// uniformly.
+    ///
+    /// The layout keeps its aspect ratio and is centred, leaving equal
+    /// margins along the sides that do not fill (letterboxing). The bounding
+    /// rectangle, regions, exclusions and shape are scaled with it.
+    #[must_use]
+    pub fn letterbox(&self, width: f32, height: f32) -> Self {
+        let s = (width / self.canvas.width()).min(height / self.canvas.height());
+        let margin_x = s.mul_add(-self.canvas.width(), width) / 2_f32;
+        let margin_y = s.mul_add(-self.canvas.height(), height) / 2_f32;
+        let offset = Point2d {
+            x: s.mul_add(-self.canvas.xmin, margin_x),
+            y: s.mul_add(-self.canvas.ymin, margin_y),
+        };
+        let mut grid = self.resized(width, height, s, s, &offset);
+        grid.blocks = self
+            .blocks
+            .iter()
+            .map(|block| block.scale(s, &offset))
+            .collect();
+        grid.occupancy_rebuild();
+        grid.dropped = self.dropped;
+        grid.attempts = self.attempts;
+        grid
+    }
+
+    /// Returns a grid of another size, placing each block again near the
+    /// same position relative to the canvas.
+    ///
+    /// Font-sizes are scaled so each block covers the same fraction of the
+    /// canvas, and orientations are kept. Largest first, a block that no
+    /// longer fits where it was searches outward along a spiral, and is
+    /// dropped after `max_attempts` candidates. Much faster than a new
+    /// layout, and the cloud keeps its look across aspect ratios.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn relayout(&self, width: f32, height: f32, max_attempts: usize) -> Self {
+        let sx = width / self.canvas.width();
+        let sy = height / self.canvas.height();
+        let s = (sx * sy).sqrt();
+        let offset = Point2d {
+            x: -sx * self.canvas.xmin,
+            y: -sy * self.canvas.ymin,
+        };
+        let mut grid = self.resized(width, height, sx, sy, &offset);
+        grid.dropped = self.dropped;
+        grid.attempts = self.attempts;
+
+        let mut order = (0..self.blocks.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let area_a = self.blocks[*a].bounds().area();
+            let area_b = self.blocks[*b].bounds().area();
+            area_b.total_cmp(&area_a)
+        });
+
+        for i in order {
+            let block = &self.blocks[i];
+            // Scaled about its center, which keeps its relative position.
+            let center = block.bounds().center();
+            let target = center.stretch(sx, sy, &offset);
+            let block = block.scale(
+                s,
+                &Point2d {
+                    x: s.mul_add(-center.x, target.x),
+                    y: s.mul_add(-center.y, target.y),
+                },
+            );
+
+            let bounds = block.bounds();
+            let spacing = bounds.width().min(bounds.height()) / 2_f32;
+            let found = (0..max_attempts).find_map(|k| {
+                let theta = k as f32 * SPIRAL_STEP;
+                let r = spacing * theta / TAU;
+                let candidate = block.translate(r * theta.cos(), r * theta.sin());
+                grid.is_valid_move(&candidate).then_some((k, candidate))
+            });
+            match found {
+                Some((k, candidate)) => {
+                    grid.attempts += k + 1;
+                    grid.push_block(candidate);
+                }
+                None => grid.dropped += 1,
             }
         }
-        false
+        grid
+    }
+
+    /// Returns the smallest rectangle holding every block, `None` when
+    /// nothing is placed.
+    #[must_use]
+    pub fn content_bounds(&self) -> Option<Rect> {
+        block::content_bounds(&self.blocks)
+    }
+
+    /// Returns the block under the point, the last drawn when several are.
+    ///
+    /// The bounding box of the block is tested, whatever its orientation.
+    /// For many queries against the same layout use `index()`.
+    #[must_use]
+    pub fn block_at(&self, p: &Point2d) -> Option<&Block> {
+        self.blocks
+            .iter()
+            .rev()
+            .find(|block| block.bounds().contains(p))
+    }
+
+    /// Returns the blocks sharing some area with the rectangle, in the order
+    /// drawn.
+    #[must_use]
+    pub fn blocks_in(&self, rect: &Rect) -> Vec<&Block> {
+        self.blocks
+            .iter()
+            .filter(|block| block.bounds().intersects(rect))
+            .collect()
+    }
+
+    /// Returns a spatial index of the blocks, valid until they change.
+    ///
+    /// Cells are sized to hold about one block each.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn index(&self) -> BlockIndex<'_> {
+        let n = self.blocks.len().max(1) as f32;
+        let cell = (self.canvas.area() / n).sqrt();
+        BlockIndex::new(&self.blocks, &self.canvas, cell)
+    }
+
+    /// Returns measures of the quality of the layout so far.
+    #[must_use]
+    pub fn stats(&self) -> LayoutStats {
+        LayoutStats::new(
+            &self.blocks,
+            self.dropped,
+            self.attempts,
+            &self.canvas,
+            &self.bounds,
+        )
     }
 
     /// Converts a string into list of (text,weight) pairs.
@@ -107,27 +647,233 @@ impl Grid {
         separated_list1(char(' '), Self::parse_text_weight)(input)
     }
 
-    // Is a point inside the bounding rectangle.
-    fn is_inside(&self, p: &Point2d) -> bool {
-        p.x > self.xmin && p.x < self.xmax && p.y > self.ymin && p.y < self.ymax
+    fn place_block_in_with(
+        &mut self,
+        text: &str,
+        area: f32,
+        region: &str,
+        max_attempts: usize,
+        interrupt: &Interrupt,
+    ) -> Placement {
+        if let Some(rect) = self.region(region) {
+            self.place_block_within(text, area, rect, max_attempts, interrupt)
+        } else {
+            self.dropped += 1;
+            Placement::Dropped
+        }
+    }
+
+    fn place_block_within(
+        &mut self,
+        text: &str,
+        area: f32,
+        rect: Rect,
+        max_attempts: usize,
+        interrupt: &Interrupt,
+    ) -> Placement {
+        // Give a block a limited number of attempts to get placed.
+        //
+        // Candidates are drawn in batches, the first candidate of a batch that
+        // fits wins. So the choice does not depend on the order in which the
+        // candidates are tested.
+        let mut tried = 0;
+        while tried < max_attempts {
+            if interrupt.is_interrupted() {
+                return Placement::Interrupted;
+            }
+            let candidates = (0..BATCH_SIZE.min(max_attempts - tried))
+                .map(|_| {
+                    let origin = self.point_at_random(&rect);
+                    Block::new_randomize_orientation(text.to_string(), area, &origin, &mut self.rng)
+                })
+                .collect::<Vec<_>>();
+
+            if let Some(i) = self.first_fit(&rect, &candidates) {
+                self.attempts += tried + i + 1;
+                self.push_block(candidates.into_iter().nth(i).expect("index from the batch"));
+                return Placement::Placed;
+            }
+            tried += candidates.len();
+        }
+        self.dropped += 1;
+        Placement::Dropped
+    }
+
+    // Index of the first candidate that fits.
+    #[cfg(not(feature = "parallel"))]
+    fn first_fit(&self, rect: &Rect, candidates: &[Block]) -> Option<usize> {
+        self.first_fit_sequential(rect, candidates)
+    }
+
+    // Index of the first candidate that fits, candidates are tested in parallel.
+    #[cfg(feature = "parallel")]
+    fn first_fit(&self, rect: &Rect, candidates: &[Block]) -> Option<usize> {
+        #[cfg(test)]
+        if test::SEQUENTIAL.with(Cell::get) {
+            return self.first_fit_sequential(rect, candidates);
+        }
+        candidates
+            .par_iter()
+            .position_first(|block| self.is_placeable(rect, block))
+    }
+
+    // Index of the first candidate that fits, candidates are tested in turn.
+    #[cfg(any(test, not(feature = "parallel")))]
+    fn first_fit_sequential(&self, rect: &Rect, candidates: &[Block]) -> Option<usize> {
+        candidates
+            .iter()
+            .position(|block| self.is_placeable(rect, block))
+    }
+
+    // Block must be inside the rectangle, and clear of everything else.
+    fn is_placeable(&self, rect: &Rect, block: &Block) -> bool {
+        rect.contains_rect(&block.bounds())
+            && self.is_inside_shape(block)
+            && !self.is_excluded(block)
+            && !self.is_any_block_overlapping(block)
+    }
+
+    // Is the whole block inside the outline, if any.
+    fn is_inside_shape(&self, block: &Block) -> bool {
+        self.shape.iter().all(|shape| shape.contains_block(block))
+    }
+
+    // Does the block overlap any exclusion zone.
+    fn is_excluded(&self, block: &Block) -> bool {
+        let bounds = block.bounds();
+        self.exclusions.iter().any(|rect| rect.intersects(&bounds))
     }
 
-    // Point is limited to the bounding rectangle.
-    fn point_at_random(&mut self) -> Point2d {
-        let x = self.rng.gen_range(self.xmin..self.xmax);
-        let y = self.rng.gen_range(self.ymin..self.ymax);
+    // A placement rectangle must have area and lie on the canvas.
+    fn validate(&self, rect: &Rect) -> Result<(), BoundsError> {
+        // Written to also reject NaN.
+        let has_area = rect.xmin < rect.xmax && rect.ymin < rect.ymax;
+        if !has_area {
+            return Err(BoundsError::Empty);
+        }
+        let on_canvas = rect.xmin >= self.canvas.xmin
+            && rect.xmax <= self.canvas.xmax
+            && rect.ymin >= self.canvas.ymin
+            && rect.ymax <= self.canvas.ymax;
+        if !on_canvas {
+            return Err(BoundsError::OutsideCanvas);
+        }
+        Ok(())
+    }
+
+    // Point is limited to the rectangle.
+    fn point_at_random(&mut self, rect: &Rect) -> Point2d {
+        let x = self.rng.gen_range(rect.xmin..rect.xmax);
+        let y = self.rng.gen_range(rect.ymin..rect.ymax);
         Point2d { x, y }
     }
 
     // Check candidate block over all existing blocks.
     fn is_any_block_overlapping(&self, test_block: &Block) -> bool {
-        for block in &self.blocks {
-            // is candidate bottom-left inside block.
-            if block.is_overlapping(test_block) {
-                return true;
+        if let Some(occupancy) = &self.occupancy {
+            return !occupancy.is_free(&self.sprite(test_block));
+        }
+
+        let test_bounds = self.collision_bounds(test_block);
+        self.blocks
+            .iter()
+            .any(|block| self.collision_bounds(block).intersects(&test_bounds))
+    }
+
+    // The bounding box inflated by the padding.
+    fn collision_bounds(&self, block: &Block) -> Rect {
+        block.bounds().inflate(self.padding.margin(block))
+    }
+
+    // The glyph coverage inflated by the padding.
+    fn sprite(&self, block: &Block) -> Vec<Rect> {
+        let margin = self.padding.margin(block);
+        block
+            .glyph_rects()
+            .iter()
+            .map(|rect| rect.inflate(margin))
+            .collect()
+    }
+
+    fn push_block(&mut self, block: Block) {
+        self.insert_block(self.blocks.len(), block);
+    }
+
+    fn remove_block(&mut self, index: usize) -> Block {
+        let block = self.blocks.remove(index);
+        if self.occupancy.is_some() {
+            let sprite = self.sprite(&block);
+            if let Some(occupancy) = &mut self.occupancy {
+                occupancy.clear(&sprite);
             }
         }
-        false
+        block
+    }
+
+    fn insert_block(&mut self, index: usize, block: Block) {
+        if self.occupancy.is_some() {
+            let sprite = self.sprite(&block);
+            if let Some(occupancy) = &mut self.occupancy {
+                occupancy.fill(&sprite);
+            }
+        }
+        self.blocks.insert(index, block);
+    }
+
+    // Can a block being compacted occupy this position.
+    fn is_valid_move(&self, block: &Block) -> bool {
+        self.canvas.contains_rect(&block.bounds())
+            && self.is_inside_shape(block)
+            && !self.is_excluded(block)
+            && !self.is_any_block_overlapping(block)
+    }
+
+    // A copy of the settings, without blocks, on a canvas of another size.
+    //
+    // The rectangles and shape are stretched, lengths scale with the smaller
+    // of the two scales.
+    fn resized(&self, width: f32, height: f32, sx: f32, sy: f32, offset: &Point2d) -> Self {
+        let s = sx.min(sy);
+        let mut grid = Self::new_with_rng(width, height, self.rng.clone());
+        grid.bounds = self.bounds.stretch(sx, sy, offset);
+        grid.shape = self
+            .shape
+            .as_ref()
+            .map(|shape| shape.stretch(sx, sy, offset));
+        grid.regions = self
+            .regions
+            .iter()
+            .map(|(name, rect)| (name.clone(), rect.stretch(sx, sy, offset)))
+            .collect();
+        grid.exclusions = self
+            .exclusions
+            .iter()
+            .map(|rect| rect.stretch(sx, sy, offset))
+            .collect();
+        grid.padding = match self.padding {
+            Padding::Absolute(gap) => Padding::Absolute(gap * s),
+            padding => padding,
+        };
+        grid.collision = match self.collision {
+            Collision::Glyph { cell } => Collision::Glyph { cell: cell * s },
+            collision @ Collision::Rectangle => collision,
+        };
+        grid.occupancy_rebuild();
+        grid
+    }
+
+    // Rasterise all placed blocks.
+    fn occupancy_rebuild(&mut self) {
+        self.occupancy = match self.collision {
+            Collision::Rectangle => None,
+            Collision::Glyph { cell } => {
+                let mut occupancy = Occupancy::new(&self.canvas, cell);
+                for block in &self.blocks {
+                    occupancy.fill(&self.sprite(block));
+                }
+                Some(occupancy)
+            }
+        };
     }
 
     fn parse_text_weight(input: &str) -> IResult<&str, TextWeight> {
@@ -143,6 +889,7 @@ impl Grid {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::Orientation;
 
     #[test]
     fn parse_text_weight() {
@@ -157,4 +904,361 @@ mod test {
         let expected = vec![TextWeight("apple", 2), TextWeight("bubble", 10)];
         assert_eq!(Grid::parse_pairs("apple,2 bubble,10"), Ok(("", expected)));
     }
+
+    #[test]
+    fn place_in_region() {
+        let mut grid = Grid::new(800_f32, 600_f32);
+        grid.region_add("centre", Rect::new(300_f32, 500_f32, 200_f32, 400_f32))
+            .unwrap();
+        // Reserve a header strip.
+        grid.exclusion_add(Rect::new(0_f32, 800_f32, 0_f32, 100_f32));
+
+        for _ in 0..20 {
+            grid.place_block_in("CENTRE", 400_f32, "centre");
+            grid.place_block("ANYWHERE", 400_f32);
+        }
+        assert!(!grid.place_block_in("NOWHERE", 400_f32, "missing"));
+
+        for block in &grid.blocks {
+            assert!(block.top_right.y >= 100_f32);
+            if block.text == "CENTRE" {
+                assert!(block.bottom_left.x >= 300_f32 && block.top_right.x <= 500_f32);
+                assert!(block.top_right.y >= 200_f32 && block.bottom_left.y <= 400_f32);
+            }
+        }
+    }
+
+    #[test]
+    fn bounding_rectangle_validation() {
+        let mut grid = Grid::new(800_f32, 600_f32);
+
+        // Touching the edge of the canvas is allowed.
+        let edge = Rect::new(0_f32, 400_f32, 0_f32, 600_f32);
+        assert_eq!(grid.try_bounding_rectangle_set(edge), Ok(()));
+        assert_eq!(grid.bounding_rectangle(), edge);
+
+        let inverted = Rect::new(400_f32, 100_f32, 0_f32, 600_f32);
+        assert_eq!(
+            grid.try_bounding_rectangle_set(inverted),
+            Err(BoundsError::Empty)
+        );
+        let outside = Rect::new(100_f32, 900_f32, 0_f32, 600_f32);
+        assert_eq!(
+            grid.try_bounding_rectangle_set(outside),
+            Err(BoundsError::OutsideCanvas)
+        );
+        // A rejected rectangle leaves the bounds untouched.
+        assert_eq!(grid.bounding_rectangle(), edge);
+    }
+
+    #[test]
+    fn padding_keeps_gap() {
+        let mut grid = Grid::new(400_f32, 400_f32);
+        grid.padding_set(Padding::Absolute(10_f32));
+        for _ in 0..40 {
+            grid.place_block("GAP", 900_f32);
+        }
+
+        for (i, a) in grid.blocks.iter().enumerate() {
+            for b in grid.blocks.iter().skip(i + 1) {
+                // Allow for rounding.
+                let inflated = a.bounds().inflate(9.99_f32);
+                assert!(!inflated.intersects(&b.bounds()));
+            }
+        }
+    }
+
+    #[test]
+    fn glyph_collision_uses_space_above_lowercase() {
+        let mut grid = Grid::new(400_f32, 400_f32);
+        // A lowercase word, 100 units high.
+        grid.blocks.push(Block {
+            text: String::from("aaaa"),
+            top_right: Point2d {
+                x: 300_f32,
+                y: 100_f32,
+            },
+            bottom_left: Point2d {
+                x: 100_f32,
+                y: 200_f32,
+            },
+            orientation: Orientation::Horizontal,
+            font_size: 100_f32,
+            weight: 0,
+            style: None,
+            href: None,
+            title: None,
+        });
+        // A small word in the top third of its rectangle.
+        let small = Block {
+            text: String::from("A"),
+            top_right: Point2d {
+                x: 140_f32,
+                y: 105_f32,
+            },
+            bottom_left: Point2d {
+                x: 120_f32,
+                y: 130_f32,
+            },
+            orientation: Orientation::Horizontal,
+            font_size: 25_f32,
+            weight: 0,
+            style: None,
+            href: None,
+            title: None,
+        };
+
+        assert!(grid.is_any_block_overlapping(&small));
+        grid.collision_set(Collision::Glyph { cell: 1_f32 });
+        assert!(!grid.is_any_block_overlapping(&small));
+
+        // Unusable cell sizes fall back to one unit.
+        for cell in [0_f32, -1_f32, f32::NAN, f32::INFINITY] {
+            grid.collision_set(Collision::Glyph { cell });
+            assert!(!grid.is_any_block_overlapping(&small));
+        }
+    }
+
+    #[test]
+    fn compact_keeps_cells_shared_with_a_neighbour() {
+        let block = |text: &str, xmin: f32, xmax: f32| Block {
+            text: String::from(text),
+            top_right: Point2d {
+                x: xmax,
+                y: 100_f32,
+            },
+            bottom_left: Point2d {
+                x: xmin,
+                y: 200_f32,
+            },
+            orientation: Orientation::Horizontal,
+            font_size: 100_f32,
+            weight: 0,
+            style: None,
+            href: None,
+            title: None,
+        };
+        let mut grid = Grid::new(600_f32, 400_f32);
+        // Touching, so both round out to the column of cells from 294 to 301.
+        grid.blocks.push(block("HHHH", 100_f32, 300_f32));
+        grid.blocks.push(block("HH", 300_f32, 400_f32));
+        grid.collision_set(Collision::Glyph { cell: 7_f32 });
+
+        // The larger block is pulled toward the smaller, which it touches.
+        let target = grid.blocks[1].bounds().center();
+        grid.compact(&target, 5);
+        assert!(!grid.blocks[0].bounds().intersects(&grid.blocks[1].bounds()));
+        assert!((grid.blocks[0].bounds().xmax - 300_f32).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn stats() {
+        let mut grid = Grid::new(800_f32, 600_f32);
+        for _ in 0..10 {
+            grid.place_block("STATS", 1000_f32);
+        }
+        // Larger than the canvas.
+        assert!(!grid.place_block("HUGE", 800_f32 * 600_f32));
+
+        let stats = grid.stats();
+        assert_eq!(stats.placed, 10);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.horizontal + stats.vertical90 + stats.vertical270, 10);
+        assert!(stats.mean_attempts >= 1_f32);
+        assert!(stats.fill_ratio > 0_f32 && stats.fill_ratio < 1_f32);
+        assert!((stats.placement_rate() - 10_f32 / 11_f32).abs() < 1e-6);
+    }
+
+    #[test]
+    fn compact_toward_center() {
+        let mut grid = Grid::new(800_f32, 600_f32);
+        for _ in 0..20 {
+            grid.place_block("COMPACT", 2000_f32);
+        }
+        let target = grid.canvas().center();
+        let spread = |grid: &Grid| -> f32 {
+            grid.blocks
+                .iter()
+                .map(|b| {
+                    let c = b.bounds().center();
+                    (c.x - target.x).hypot(c.y - target.y)
+                })
+                .sum()
+        };
+
+        let before = spread(&grid);
+        let n_blocks = grid.blocks.len();
+        grid.compact(&target, 10);
+
+        assert_eq!(grid.blocks.len(), n_blocks);
+        assert!(spread(&grid) < before);
+        for (i, a) in grid.blocks.iter().enumerate() {
+            for b in grid.blocks.iter().skip(i + 1) {
+                assert!(!a.bounds().intersects(&b.bounds()));
+            }
+        }
+    }
+
+    #[test]
+    fn cancelled_layout_is_incomplete() {
+        let mut grid = Grid::new(800_f32, 600_f32);
+        let words = vec![Word::new("apple", 2), Word::new("bubble", 10)];
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let options = LayoutOptions {
+            cancel: Some(cancel),
+            ..LayoutOptions::default()
+        };
+        let result = grid.layout(&words, &options);
+        assert!(!result.complete);
+        assert_eq!(result.placed, 0);
+        assert!(grid.blocks.is_empty());
+
+        let result = grid.layout(&words, &LayoutOptions::default());
+        assert!(result.complete);
+        assert_eq!(result.placed + result.dropped, 2);
+    }
+
+    #[test]
+    fn observer_sees_every_word() {
+        let mut grid = Grid::new(800_f32, 600_f32);
+        let words = vec![
+            Word::new("apple", 2),
+            Word::new("bubble", 10),
+            Word::new("cherry", 5),
+        ];
+
+        let mut events = vec![];
+        let result = grid.layout_with_observer(&words, &LayoutOptions::default(), |event| {
+            let text = match event {
+                PlacementEvent::Placed { block, .. } => block.text.clone(),
+                PlacementEvent::Dropped { text, .. } => (*text).to_string(),
+            };
+            events.push((text, event.progress()));
+        });
+
+        assert_eq!(result.placed + result.dropped, 3);
+        assert_eq!(events.len(), 3);
+        // Largest weight first.
+        assert_eq!(events[0].0, "bubble");
+        assert!((events[2].1 - 1_f32).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn seeded_layout_is_repeatable() {
+        let words = vec![
+            Word::new("apple", 2),
+            Word::new("bubble", 10),
+            Word::new("cherry", 5),
+            Word::new("damson", 7),
+        ];
+        let run = |seed| {
+            let mut grid = Grid::new_with_seed(400_f32, 300_f32, seed);
+            grid.layout(&words, &LayoutOptions::default());
+            grid.blocks
+        };
+
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[cfg(feature = "parallel")]
+    thread_local! {
+        // Makes `first_fit` test the candidates in turn.
+        pub static SEQUENTIAL: Cell<bool> = const { Cell::new(false) };
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_matches_sequential() {
+        let words = (1..=40)
+            .map(|i| Word::new(format!("word{i}"), i))
+            .collect::<Vec<_>>();
+        let mut grid = Grid::new_with_seed(300_f32, 200_f32, 11);
+        grid.layout(&words, &LayoutOptions::default());
+
+        // Batches drawn the way a layout draws them, on a crowded canvas.
+        let rect = grid.bounding_rectangle();
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..50 {
+            let candidates = (0..BATCH_SIZE)
+                .map(|_| {
+                    let origin = Point2d {
+                        x: rng.gen_range(rect.xmin..rect.xmax),
+                        y: rng.gen_range(rect.ymin..rect.ymax),
+                    };
+                    Block::new_randomize_orientation(
+                        String::from("fit"),
+                        400_f32,
+                        &origin,
+                        &mut rng,
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                grid.first_fit(&rect, &candidates),
+                grid.first_fit_sequential(&rect, &candidates)
+            );
+        }
+
+        let run = |sequential| {
+            SEQUENTIAL.with(|cell| cell.set(sequential));
+            let mut grid = Grid::new_with_seed(300_f32, 200_f32, 11);
+            grid.layout(&words, &LayoutOptions::default());
+            grid.blocks
+        };
+        assert_eq!(run(false), run(true));
+    }
+
+    #[test]
+    fn resize_a_layout() {
+        let mut grid = Grid::new_with_seed(800_f32, 600_f32, 3);
+        let words = (1..=20)
+            .map(|i| Word::new(format!("word{i}"), i))
+            .collect::<Vec<_>>();
+        grid.layout(&words, &LayoutOptions::default());
+        let n = grid.blocks.len();
+
+        // Half the size, centred vertically.
+        let boxed = grid.letterbox(400_f32, 400_f32);
+        assert_eq!(boxed.blocks.len(), n);
+        for (before, after) in grid.blocks.iter().zip(&boxed.blocks) {
+            assert!((after.bottom_left.x - before.bottom_left.x / 2_f32).abs() < 1e-3);
+            assert!((after.bottom_left.y - (before.bottom_left.y / 2_f32 + 50_f32)).abs() < 1e-3);
+            assert!((after.font_size - before.font_size / 2_f32).abs() < 1e-3);
+        }
+
+        // Portrait, every block stays on the canvas and clear of the others.
+        let portrait = grid.relayout(300_f32, 600_f32, MAX_ATTEMPTS);
+        assert_eq!(
+            portrait.blocks.len() + portrait.stats().dropped,
+            n + grid.stats().dropped
+        );
+        for (i, a) in portrait.blocks.iter().enumerate() {
+            assert!(portrait.canvas().contains_rect(&a.bounds()));
+            for b in &portrait.blocks[i + 1..] {
+                assert!(!a.bounds().intersects(&b.bounds()));
+            }
+        }
+    }
+
+    #[test]
+    fn style_stays_with_its_word() {
+        let mut grid = Grid::new(800_f32, 600_f32);
+        let words = vec![
+            Word::new("apple", 2).style_set(Style::fill("green")),
+            Word::new("bubble", 10),
+            Word::new("cherry", 5).style_set(Style::fill("red")),
+        ];
+        grid.layout(&words, &LayoutOptions::default());
+
+        for block in &grid.blocks {
+            let expected = words
+                .iter()
+                .find(|word| word.text == block.text)
+                .and_then(|word| word.style.clone());
+            assert_eq!(block.style, expected);
+        }
+    }
 }
@@ -0,0 +1,150 @@
+use core::ops::RangeInclusive;
+
+use crate::block::Block;
+use crate::Point2d;
+use crate::Rect;
+
+/// Buckets the blocks of a layout by position, for repeated hit testing.
+///
+/// The area is divided into square cells, each listing the blocks whose
+/// bounding box touches it. Blocks reaching beyond the area are listed in
+/// the cells along its edge, so every query is exact.
+///
+/// ```text
+/// let index = grid.index();
+/// let hovered = index.block_at(&Point2d { x: 120_f32, y: 40_f32 });
+/// ```
+#[derive(Debug)]
+pub struct BlockIndex<'a> {
+    blocks: &'a [Block],
+    area: Rect,
+    cell: f32,
+    columns: usize,
+    rows: usize,
+    // Indices of the blocks touching each cell, row by row.
+    buckets: Vec<Vec<usize>>,
+}
+
+impl<'a> BlockIndex<'a> {
+    /// Returns an index of the blocks, given the area to divide and the side
+    /// of a cell.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn new(blocks: &'a [Block], area: &Rect, cell: f32) -> Self {
+        let cell = if cell > 0_f32 { cell } else { 1_f32 };
+        let columns = (area.width() / cell).ceil().max(1_f32) as usize;
+        let rows = (area.height() / cell).ceil().max(1_f32) as usize;
+        let mut index = Self {
+            blocks,
+            area: *area,
+            cell,
+            columns,
+            rows,
+            buckets: vec![vec![]; columns * rows],
+        };
+        for (i, block) in blocks.iter().enumerate() {
+            let (columns, rows) = index.cells(&block.bounds());
+            for j in rows {
+                for k in columns.clone() {
+                    index.buckets[j * index.columns + k].push(i);
+                }
+            }
+        }
+        index
+    }
+
+    /// Returns the block under the point, the last drawn when several are.
+    #[must_use]
+    pub fn block_at(&self, p: &Point2d) -> Option<&'a Block> {
+        let (column, row) = (self.column(p.x), self.row(p.y));
+        self.buckets[row * self.columns + column]
+            .iter()
+            .rev()
+            .map(|i| &self.blocks[*i])
+            .find(|block| block.bounds().contains(p))
+    }
+
+    /// Returns the blocks sharing some area with the rectangle, in the order
+    /// drawn.
+    #[must_use]
+    pub fn blocks_in(&self, rect: &Rect) -> Vec<&'a Block> {
+        let (columns, rows) = self.cells(rect);
+        let mut found = rows
+            .flat_map(|j| {
+                columns
+                    .clone()
+                    .flat_map(move |k| self.buckets[j * self.columns + k].iter().copied())
+            })
+            .filter(|i| self.blocks[*i].bounds().intersects(rect))
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        found.dedup();
+        found.into_iter().map(|i| &self.blocks[i]).collect()
+    }
+
+    // The cells touched by the rectangle, clamped to the area.
+    fn cells(&self, rect: &Rect) -> (RangeInclusive<usize>, RangeInclusive<usize>) {
+        (
+            self.column(rect.xmin)..=self.column(rect.xmax),
+            self.row(rect.ymin)..=self.row(rect.ymax),
+        )
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn column(&self, x: f32) -> usize {
+        let k = ((x - self.area.xmin) / self.cell).floor().max(0_f32) as usize;
+        k.min(self.columns - 1)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn row(&self, y: f32) -> usize {
+        let j = ((y - self.area.ymin) / self.cell).floor().max(0_f32) as usize;
+        j.min(self.rows - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Orientation;
+
+    #[test]
+    fn every_orientation() {
+        let blocks = [
+            (Orientation::Horizontal, 100_f32),
+            (Orientation::Vertical90, 400_f32),
+            (Orientation::Vertical270, 700_f32),
+        ]
+        .into_iter()
+        .map(|(orientation, x)| {
+            Block::new(
+                String::from("word"),
+                2000_f32,
+                &Point2d { x, y: 300_f32 },
+                orientation,
+            )
+        })
+        .collect::<Vec<_>>();
+        let canvas = Rect::new(0_f32, 800_f32, 0_f32, 600_f32);
+        let index = BlockIndex::new(&blocks, &canvas, 50_f32);
+
+        for block in &blocks {
+            let center = block.bounds().center();
+            assert_eq!(index.block_at(&center), Some(block));
+        }
+        assert_eq!(
+            index.block_at(&Point2d {
+                x: 10_f32,
+                y: 10_f32
+            }),
+            None
+        );
+
+        let all = index.blocks_in(&canvas);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2], &blocks[2]);
+        // Queries beyond the area are clamped to its edge.
+        let outside = Rect::new(-100_f32, 0_f32, -100_f32, 0_f32);
+        assert!(index.blocks_in(&outside).is_empty());
+    }
+}
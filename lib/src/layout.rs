@@ -0,0 +1,226 @@
+use core::ops::RangeInclusive;
+use core::time::Duration;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::block::Block;
+use crate::grid::TextWeight;
+use crate::scale::SizeScale;
+use crate::style::Style;
+
+/// A word to place.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Word {
+    /// The text.
+    pub text: String,
+    /// Sets the size of the block, see `SizeScale`.
+    pub weight: u32,
+    /// Presentation, copied onto the block.
+    #[serde(default)]
+    pub style: Option<Style>,
+    /// Link followed when the word is clicked, copied onto the block.
+    #[serde(default)]
+    pub href: Option<String>,
+    /// Tooltip, copied onto the block.
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+impl Word {
+    /// Returns an unstyled word.
+    #[must_use]
+    pub fn new(text: impl Into<String>, weight: u32) -> Self {
+        Self {
+            text: text.into(),
+            weight,
+            style: None,
+            href: None,
+            title: None,
+        }
+    }
+
+    /// Sets the presentation of the word.
+    #[must_use]
+    pub fn style_set(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Sets the link followed when the word is clicked.
+    #[must_use]
+    pub fn href_set(mut self, href: impl Into<String>) -> Self {
+        self.href = Some(href.into());
+        self
+    }
+
+    /// Sets the tooltip.
+    #[must_use]
+    pub fn title_set(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+}
+
+impl From<&TextWeight<'_>> for Word {
+    fn from(TextWeight(text, weight): &TextWeight) -> Self {
+        Self::new(*text, *weight)
+    }
+}
+
+/// The number of candidates generated for a word before it is dropped.
+pub const MAX_ATTEMPTS: usize = 2000;
+
+/// Controls how a list of (text, weight) pairs is placed on a grid.
+///
+/// See `Grid::layout`.
+#[derive(Clone, Debug)]
+pub struct LayoutOptions {
+    /// Maps each weight onto the area of a block.
+    pub size_scale: SizeScale,
+    /// Assigns words to the named regions of the grid.
+    ///
+    /// The first matching rule wins. Unmatched words are placed
+    /// inside the bounding rectangle.
+    pub regions: Vec<RegionRule>,
+    /// The number of candidates generated for a word before it is dropped.
+    pub max_attempts: usize,
+    /// Wall clock time after which the layout stops.
+    ///
+    /// `std::time::Instant` is unavailable on `wasm32-unknown-unknown`,
+    /// leave this unset there.
+    pub time_budget: Option<Duration>,
+    /// Stops the layout when cancelled from another thread.
+    pub cancel: Option<CancelToken>,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            size_scale: SizeScale::default(),
+            regions: vec![],
+            max_attempts: MAX_ATTEMPTS,
+            time_budget: None,
+            cancel: None,
+        }
+    }
+}
+
+impl LayoutOptions {
+    /// Returns the name of the region assigned to the word, if any.
+    #[must_use]
+    pub fn region_for(&self, word: &Word) -> Option<&str> {
+        self.regions
+            .iter()
+            .find(|rule| rule.is_match(word))
+            .map(RegionRule::region)
+    }
+}
+
+/// Assigns words to a named region of the grid.
+///
+/// See `Grid::region_add`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RegionRule {
+    /// A single word.
+    Word {
+        /// The text to match.
+        text: String,
+        /// The name of the region.
+        region: String,
+    },
+    /// A weight band.
+    Weights {
+        /// The weights to match.
+        weights: RangeInclusive<u32>,
+        /// The name of the region.
+        region: String,
+    },
+}
+
+impl RegionRule {
+    /// Returns the name of the region.
+    #[must_use]
+    pub fn region(&self) -> &str {
+        match self {
+            Self::Word { region, .. } | Self::Weights { region, .. } => region,
+        }
+    }
+
+    fn is_match(&self, word: &Word) -> bool {
+        match self {
+            Self::Word { text, .. } => *text == word.text,
+            Self::Weights { weights, .. } => weights.contains(&word.weight),
+        }
+    }
+}
+
+/// A flag used to stop a layout running on another thread.
+///
+/// Clones share the same flag. The flag is checked between batches of
+/// candidates.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Returns a token that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request that the layout stops.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Has the layout been asked to stop.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The outcome of `Grid::layout`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LayoutResult {
+    /// Number of words placed.
+    pub placed: usize,
+    /// Number of words that did not fit.
+    pub dropped: usize,
+    /// False when the time budget ran out or the layout was cancelled,
+    /// before every word was tried.
+    pub complete: bool,
+}
+
+/// Reported as each word is processed by `Grid::layout_with_observer`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlacementEvent<'a> {
+    /// The word has been added to the grid.
+    Placed {
+        /// The new block.
+        block: &'a Block,
+        /// Fraction of the words processed so far.
+        progress: f32,
+    },
+    /// The word did not fit.
+    Dropped {
+        /// The dropped word.
+        text: &'a str,
+        /// Fraction of the words processed so far.
+        progress: f32,
+    },
+}
+
+impl PlacementEvent<'_> {
+    /// Returns the fraction of the words processed so far.
+    #[must_use]
+    pub const fn progress(&self) -> f32 {
+        match self {
+            Self::Placed { progress, .. } | Self::Dropped { progress, .. } => *progress,
+        }
+    }
+}
@@ -9,10 +9,11 @@
 //! A library for packing text close together.
 //!
 
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
 use serde::Deserialize;
 use serde::Serialize;
 
+#[cfg(feature = "leptos")]
 extern crate leptos;
 extern crate nom;
 extern crate serde;
@@ -20,17 +21,51 @@ extern crate serde;
 /// A block is a collection of characters with associated data.
 pub mod block;
 
+/// Points and rectangles on the canvas.
+pub mod geometry;
+
+/// Colour schemes for blocks.
+pub mod color;
+
 /// A collection of block data.
 pub mod grid;
 
-/// Primitive  representation of a point on the canvas.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-pub struct Point2d {
-    /// x coordinate
-    pub x: f32,
-    /// y coordinate
-    pub y: f32,
-}
+/// Finding the blocks at a position.
+pub mod index;
+
+/// Options controlling the placement of a list of words.
+pub mod layout;
+
+/// Maps the weight of a word onto the size of its block.
+pub mod scale;
+
+/// Outlines constraining the placement of blocks.
+pub mod shape;
+
+/// Measures of the quality of a layout.
+pub mod stats;
+
+/// Presentation of a word.
+pub mod style;
+
+/// Renders blocks as SVG elements.
+pub mod svg;
+
+/// Animating one layout into another.
+pub mod transition;
+
+/// Rendering blocks with leptos.
+#[cfg(feature = "leptos")]
+pub mod view;
+
+/// Bindings for use from JavaScript.
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+mod occupancy;
+
+pub use geometry::Point2d;
+pub use geometry::Rect;
 
 /// State of the text object.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -46,7 +81,7 @@ pub enum Orientation {
 }
 
 impl Orientation {
-    fn at_random(rng: &mut ThreadRng) -> Self {
+    fn at_random<R: Rng + ?Sized>(rng: &mut R) -> Self {
         let i = rng.gen_range(0..3);
         if i == 0 {
             Self::Horizontal
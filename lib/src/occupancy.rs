@@ -0,0 +1,139 @@
+use core::ops::Range;
+
+use crate::Rect;
+
+/// An occupancy grid covering the canvas.
+///
+/// Each cell is a square with integer coordinates on the grid. A rectangle
+/// marks every cell it touches, so coverage errs on the side of collision.
+/// Cells count the sprites marking them, so neighbouring blocks may share a
+/// cell and clearing one leaves the cell marked by the other.
+#[derive(Clone, Debug)]
+pub struct Occupancy {
+    cell: f32,
+    origin_x: f32,
+    origin_y: f32,
+    columns: usize,
+    rows: usize,
+    counts: Vec<u16>,
+}
+
+impl Occupancy {
+    /// A cell size that is not positive and finite is replaced by 1.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn new(canvas: &Rect, cell: f32) -> Self {
+        let cell = if cell.is_finite() && cell > 0_f32 {
+            cell
+        } else {
+            1_f32
+        };
+        let columns = (canvas.width() / cell).ceil().max(1_f32) as usize;
+        let rows = (canvas.height() / cell).ceil().max(1_f32) as usize;
+        Self {
+            cell,
+            origin_x: canvas.xmin,
+            origin_y: canvas.ymin,
+            columns,
+            rows,
+            counts: vec![0; columns * rows],
+        }
+    }
+
+    /// Are all the cells touched by the sprite empty.
+    pub fn is_free(&self, sprite: &[Rect]) -> bool {
+        sprite.iter().all(|rect| {
+            let (columns, rows) = self.cells(rect);
+            rows.into_iter().all(|j| {
+                let row = j * self.columns;
+                self.counts[row + columns.start..row + columns.end]
+                    .iter()
+                    .all(|count| *count == 0)
+            })
+        })
+    }
+
+    /// Mark all the cells touched by the sprite.
+    pub fn fill(&mut self, sprite: &[Rect]) {
+        self.update(sprite, |count| count.saturating_add(1));
+    }
+
+    /// Release all the cells touched by the sprite, once filled with it.
+    pub fn clear(&mut self, sprite: &[Rect]) {
+        self.update(sprite, |count| count.saturating_sub(1));
+    }
+
+    // Apply the change to the count of every cell touched by the sprite.
+    fn update(&mut self, sprite: &[Rect], f: impl Fn(u16) -> u16) {
+        for rect in sprite {
+            let (columns, rows) = self.cells(rect);
+            for j in rows {
+                let row = j * self.columns;
+                for count in &mut self.counts[row + columns.start..row + columns.end] {
+                    *count = f(*count);
+                }
+            }
+        }
+    }
+
+    // The range of columns and rows touched by the rectangle,
+    // clipped to the grid.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn cells(&self, rect: &Rect) -> (Range<usize>, Range<usize>) {
+        let to_index = |v: f32, max: usize| (v.max(0_f32) as usize).min(max);
+
+        let i0 = to_index(
+            ((rect.xmin - self.origin_x) / self.cell).floor(),
+            self.columns,
+        );
+        let i1 = to_index(
+            ((rect.xmax - self.origin_x) / self.cell).ceil(),
+            self.columns,
+        );
+        let j0 = to_index(((rect.ymin - self.origin_y) / self.cell).floor(), self.rows);
+        let j1 = to_index(((rect.ymax - self.origin_y) / self.cell).ceil(), self.rows);
+        (i0..i1.max(i0), j0..j1.max(j0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fill_and_clear() {
+        let mut occupancy = Occupancy::new(&Rect::new(0_f32, 200_f32, 0_f32, 100_f32), 1_f32);
+        let a = [Rect::new(10_f32, 150_f32, 10_f32, 20_f32)];
+        let b = [Rect::new(60_f32, 70_f32, 19.5_f32, 30_f32)];
+        let c = [Rect::new(60_f32, 70_f32, 21_f32, 30_f32)];
+
+        assert!(occupancy.is_free(&a));
+        occupancy.fill(&a);
+        assert!(!occupancy.is_free(&a));
+        // Shares a row of cells.
+        assert!(!occupancy.is_free(&b));
+        assert!(occupancy.is_free(&c));
+
+        occupancy.clear(&a);
+        assert!(occupancy.is_free(&b));
+    }
+
+    #[test]
+    fn shared_cells_stay_marked() {
+        let mut occupancy = Occupancy::new(&Rect::new(0_f32, 200_f32, 0_f32, 100_f32), 10_f32);
+        // Both round out to the cells from 50 to 60.
+        let a = [Rect::new(10_f32, 55_f32, 10_f32, 20_f32)];
+        let b = [Rect::new(55_f32, 90_f32, 10_f32, 20_f32)];
+        let between = [Rect::new(52_f32, 58_f32, 12_f32, 18_f32)];
+
+        occupancy.fill(&a);
+        occupancy.fill(&b);
+        occupancy.clear(&a);
+        assert!(!occupancy.is_free(&between));
+        occupancy.clear(&b);
+        assert!(occupancy.is_free(&between));
+    }
+}
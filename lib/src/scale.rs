@@ -0,0 +1,172 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::block::Block;
+use crate::layout::Word;
+
+/// The curve used to map a weight onto a font size.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScaleMode {
+    /// Font size grows in proportion to the weight.
+    Linear,
+    /// Font size grows with the square root of the weight.
+    Sqrt,
+    /// Font size grows with the logarithm of the weight.
+    ///
+    /// Strongly compresses outliers.
+    Log,
+    /// Font size grows with the weight raised to the given exponent.
+    Pow(f32),
+}
+
+impl ScaleMode {
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            Self::Linear => x,
+            Self::Sqrt => x.max(0_f32).sqrt(),
+            Self::Log => x.max(0_f32).ln_1p(),
+            Self::Pow(exponent) => x.max(0_f32).powf(exponent),
+        }
+    }
+}
+
+/// Maps the weight of a (text, weight) pair onto the area of a block.
+///
+/// The weight is first mapped onto a font size, clamped to the font size
+/// range. The area is then that of a block holding the text at that size.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SizeScale {
+    mode: ScaleMode,
+    domain: [f32; 2],
+    font_size: [f32; 2],
+}
+
+impl Default for SizeScale {
+    fn default() -> Self {
+        Self {
+            mode: ScaleMode::Linear,
+            domain: [1_f32, 10_f32],
+            font_size: [12_f32, 48_f32],
+        }
+    }
+}
+
+impl SizeScale {
+    /// Returns a scale with the given mode.
+    ///
+    /// The domain defaults to weights in the range 1..=10.
+    #[must_use]
+    pub fn new(mode: ScaleMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the range of weights.
+    #[must_use]
+    pub const fn domain(&self) -> [f32; 2] {
+        self.domain
+    }
+
+    /// Sets the range of weights.
+    ///
+    /// Weights outside the domain are clamped.
+    #[must_use]
+    pub const fn domain_set(mut self, domain: [f32; 2]) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Sets the domain to the smallest and largest weight in the list.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn domain_from(self, words: &[Word]) -> Self {
+        let min = words.iter().map(|word| word.weight).min();
+        let max = words.iter().map(|word| word.weight).max();
+        match (min, max) {
+            (Some(min), Some(max)) => self.domain_set([min as f32, max as f32]),
+            _ => self,
+        }
+    }
+
+    /// Returns the minimum and maximum font size.
+    #[must_use]
+    pub const fn font_size_range(&self) -> [f32; 2] {
+        self.font_size
+    }
+
+    /// Sets the minimum and maximum font size.
+    #[must_use]
+    pub const fn font_size_range_set(mut self, font_size: [f32; 2]) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    /// Returns the font size for a given weight.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn font_size(&self, weight: u32) -> f32 {
+        let lower = self.mode.apply(self.domain[0]);
+        let upper = self.mode.apply(self.domain[1]);
+        let delta = upper - lower;
+
+        // A domain holding a single weight maps onto the mid point.
+        let t = if delta.abs() < f32::EPSILON {
+            0.5_f32
+        } else {
+            ((self.mode.apply(weight as f32) - lower) / delta).clamp(0_f32, 1_f32)
+        };
+
+        t.mul_add(self.font_size[1] - self.font_size[0], self.font_size[0])
+    }
+
+    /// Returns the area of the block holding `text` with the given weight.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn area(&self, text: &str, weight: u32) -> f32 {
+        Block::area(self.font_size(weight), text.len() as f32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{a} != {b}");
+    }
+
+    #[test]
+    fn linear_end_points() {
+        let scale = SizeScale::new(ScaleMode::Linear)
+            .domain_set([1_f32, 11_f32])
+            .font_size_range_set([10_f32, 20_f32]);
+        assert_near(scale.font_size(1), 10_f32);
+        assert_near(scale.font_size(6), 15_f32);
+        assert_near(scale.font_size(11), 20_f32);
+    }
+
+    #[test]
+    fn clamped_to_font_size_range() {
+        let scale = SizeScale::new(ScaleMode::Log)
+            .domain_set([1_f32, 10_f32])
+            .font_size_range_set([10_f32, 20_f32]);
+        assert_near(scale.font_size(0), 10_f32);
+        assert_near(scale.font_size(1000), 20_f32);
+    }
+
+    #[test]
+    fn sqrt_compresses_outliers() {
+        let linear = SizeScale::new(ScaleMode::Linear).domain_set([1_f32, 100_f32]);
+        let sqrt = SizeScale::new(ScaleMode::Sqrt).domain_set([1_f32, 100_f32]);
+        assert!(sqrt.font_size(25) > linear.font_size(25));
+    }
+
+    #[test]
+    fn area_matches_font_size() {
+        let scale = SizeScale::default();
+        let area = scale.area("TAXI", 10);
+        assert_near(Block::h(area, 4_f32), scale.font_size(10));
+    }
+}
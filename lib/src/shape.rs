@@ -0,0 +1,335 @@
+use crate::block::Block;
+use crate::Point2d;
+use crate::Rect;
+
+/// An outline to which the placement of blocks is constrained.
+///
+/// A block is only valid if its whole rectangle lies inside the shape.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// A circle.
+    Circle {
+        /// Center of the circle.
+        center: Point2d,
+        /// Radius of the circle.
+        radius: f32,
+    },
+    /// An axis aligned ellipse.
+    Ellipse {
+        /// Center of the ellipse.
+        center: Point2d,
+        /// Half width.
+        rx: f32,
+        /// Half height.
+        ry: f32,
+    },
+    /// A closed polygon, listed vertex by vertex.
+    ///
+    /// The polygon may be concave.
+    Polygon(Vec<Point2d>),
+    /// A bitmap, blocks may only cover set pixels.
+    Mask(Mask),
+}
+
+impl Shape {
+    /// Is the point inside the shape.
+    #[must_use]
+    pub fn contains_point(&self, p: &Point2d) -> bool {
+        match self {
+            Self::Circle { center, radius } => {
+                let dx = p.x - center.x;
+                let dy = p.y - center.y;
+                dx.hypot(dy) <= *radius
+            }
+            Self::Ellipse { center, rx, ry } => {
+                let dx = (p.x - center.x) / rx;
+                let dy = (p.y - center.y) / ry;
+                dx.hypot(dy) <= 1_f32
+            }
+            Self::Polygon(vertices) => is_inside_polygon(vertices, p),
+            Self::Mask(mask) => mask.contains_rect(&Rect::new(p.x, p.x, p.y, p.y)),
+        }
+    }
+
+    /// Is the whole of the block inside the shape.
+    #[must_use]
+    pub fn contains_block(&self, block: &Block) -> bool {
+        self.contains_rect(&block.bounds())
+    }
+
+    /// Is the whole of the rectangle inside the shape.
+    #[must_use]
+    pub fn contains_rect(&self, rect: &Rect) -> bool {
+        let corners = rect.corners();
+        match self {
+            // Convex shapes contain a rectangle when they contain its corners.
+            Self::Circle { .. } | Self::Ellipse { .. } => {
+                corners.iter().all(|c| self.contains_point(c))
+            }
+            // A concave outline can also cut through the rectangle
+            // between the corners.
+            Self::Polygon(vertices) => {
+                corners.iter().all(|c| is_inside_polygon(vertices, c))
+                    && !vertices
+                        .iter()
+                        .zip(vertices.iter().cycle().skip(1))
+                        .any(|(a, b)| is_segment_crossing_rect(a, b, rect))
+            }
+            Self::Mask(mask) => mask.contains_rect(rect),
+        }
+    }
+
+    // The shape with x scaled by sx and y by sy, then moved by the offset.
+    //
+    // A circle stretched unevenly becomes an ellipse.
+    pub(crate) fn stretch(&self, sx: f32, sy: f32, offset: &Point2d) -> Self {
+        match self {
+            Self::Circle { center, radius } if (sx - sy).abs() <= f32::EPSILON => Self::Circle {
+                center: center.stretch(sx, sy, offset),
+                radius: radius * sx,
+            },
+            Self::Circle { center, radius } => Self::Ellipse {
+                center: center.stretch(sx, sy, offset),
+                rx: radius * sx,
+                ry: radius * sy,
+            },
+            Self::Ellipse { center, rx, ry } => Self::Ellipse {
+                center: center.stretch(sx, sy, offset),
+                rx: rx * sx,
+                ry: ry * sy,
+            },
+            Self::Polygon(vertices) => {
+                Self::Polygon(vertices.iter().map(|v| v.stretch(sx, sy, offset)).collect())
+            }
+            Self::Mask(mask) => Self::Mask(Mask {
+                bounds: mask.bounds.stretch(sx, sy, offset),
+                ..mask.clone()
+            }),
+        }
+    }
+}
+
+/// A 1-bit bitmap stretched over a rectangle of the canvas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mask {
+    width: usize,
+    height: usize,
+    bits: Vec<bool>,
+    // The rectangle of the canvas covered by the mask.
+    bounds: Rect,
+}
+
+impl Mask {
+    /// Returns a mask from a row major list of pixels.
+    ///
+    /// Initially one pixel covers one unit of the canvas.
+    ///
+    /// # Panics
+    ///
+    /// When the number of pixels is not `width * height`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(width: usize, height: usize, bits: Vec<bool>) -> Self {
+        assert_eq!(bits.len(), width * height, "mask size mismatch");
+        Self {
+            width,
+            height,
+            bits,
+            bounds: Rect::new(0_f32, width as f32, 0_f32, height as f32),
+        }
+    }
+
+    /// Decodes a PNG image.
+    ///
+    /// A pixel is set when it is both opaque and dark, so a black
+    /// silhouette on a white or transparent background becomes the shape.
+    ///
+    /// # Errors
+    ///
+    /// When the image cannot be decoded.
+    #[cfg(feature = "png")]
+    pub fn from_png(bytes: &[u8]) -> Result<Self, png::DecodingError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+
+        let channels = info.color_type.samples();
+        let bits = buf[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|px| {
+                let (luma, alpha) = match px {
+                    [l] => (u32::from(*l), 255),
+                    [l, a] => (u32::from(*l), *a),
+                    [r, g, b] => ((u32::from(*r) + u32::from(*g) + u32::from(*b)) / 3, 255),
+                    [r, g, b, a, ..] => ((u32::from(*r) + u32::from(*g) + u32::from(*b)) / 3, *a),
+                    [] => (255, 0),
+                };
+                alpha >= 128 && luma < 128
+            })
+            .collect();
+
+        Ok(Self::new(info.width as usize, info.height as usize, bits))
+    }
+
+    /// Stretch the mask over the given rectangle of the canvas.
+    #[must_use]
+    pub const fn fit(mut self, bounds: Rect) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// Returns the state of the pixel at column `i`, row `j`.
+    #[must_use]
+    pub fn is_set(&self, i: usize, j: usize) -> bool {
+        i < self.width && j < self.height && self.bits[j * self.width + i]
+    }
+
+    // Are all the pixels covered by the rectangle set.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn contains_rect(&self, rect: &Rect) -> bool {
+        let b = &self.bounds;
+        if rect.xmin < b.xmin || rect.xmax > b.xmax || rect.ymin < b.ymin || rect.ymax > b.ymax {
+            return false;
+        }
+        let sx = b.width() / self.width as f32;
+        let sy = b.height() / self.height as f32;
+
+        let i0 = ((rect.xmin - b.xmin) / sx).floor() as usize;
+        let i1 = (((rect.xmax - b.xmin) / sx).ceil() as usize).max(i0 + 1);
+        let j0 = ((rect.ymin - b.ymin) / sy).floor() as usize;
+        let j1 = (((rect.ymax - b.ymin) / sy).ceil() as usize).max(j0 + 1);
+
+        (j0..j1).all(|j| (i0..i1).all(|i| self.is_set(i, j)))
+    }
+}
+
+// Even-odd rule.
+fn is_inside_polygon(vertices: &[Point2d], p: &Point2d) -> bool {
+    let mut inside = false;
+    for (a, b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x;
+            if p.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+// Liang-Barsky clipping: does any part of the segment lie inside the rectangle.
+fn is_segment_crossing_rect(a: &Point2d, b: &Point2d, rect: &Rect) -> bool {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let mut t0 = 0_f32;
+    let mut t1 = 1_f32;
+    for (p, q) in [
+        (-dx, a.x - rect.xmin),
+        (dx, rect.xmax - a.x),
+        (-dy, a.y - rect.ymin),
+        (dy, rect.ymax - a.y),
+    ] {
+        if p == 0_f32 {
+            if q < 0_f32 {
+                return false;
+            }
+        } else {
+            let r = q / p;
+            if p < 0_f32 {
+                t0 = t0.max(r);
+            } else {
+                t1 = t1.min(r);
+            }
+        }
+    }
+    t0 < t1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Orientation;
+
+    fn block(xmin: f32, xmax: f32, ymin: f32, ymax: f32) -> Block {
+        Block {
+            text: String::from("M"),
+            top_right: Point2d { x: xmax, y: ymin },
+            bottom_left: Point2d { x: xmin, y: ymax },
+            orientation: Orientation::Horizontal,
+            font_size: ymax - ymin,
+            weight: 0,
+            style: None,
+            href: None,
+            title: None,
+        }
+    }
+
+    #[test]
+    fn circle() {
+        let circle = Shape::Circle {
+            center: Point2d {
+                x: 100_f32,
+                y: 100_f32,
+            },
+            radius: 50_f32,
+        };
+        assert!(circle.contains_block(&block(90_f32, 110_f32, 90_f32, 110_f32)));
+        // Corners of the bounding square lie outside the circle.
+        assert!(!circle.contains_block(&block(55_f32, 145_f32, 55_f32, 145_f32)));
+    }
+
+    #[test]
+    fn concave_polygon() {
+        // A "U" shape, the notch runs down from the top between x = 40 and x = 60.
+        let u = Shape::Polygon(vec![
+            Point2d { x: 0_f32, y: 0_f32 },
+            Point2d {
+                x: 40_f32,
+                y: 0_f32,
+            },
+            Point2d {
+                x: 40_f32,
+                y: 80_f32,
+            },
+            Point2d {
+                x: 60_f32,
+                y: 80_f32,
+            },
+            Point2d {
+                x: 60_f32,
+                y: 0_f32,
+            },
+            Point2d {
+                x: 100_f32,
+                y: 0_f32,
+            },
+            Point2d {
+                x: 100_f32,
+                y: 100_f32,
+            },
+            Point2d {
+                x: 0_f32,
+                y: 100_f32,
+            },
+        ]);
+        assert!(u.contains_block(&block(10_f32, 30_f32, 10_f32, 30_f32)));
+        // All four corners are inside, but the notch cuts through the block.
+        assert!(!u.contains_block(&block(10_f32, 90_f32, 10_f32, 30_f32)));
+    }
+
+    #[test]
+    fn mask() {
+        // Left half set.
+        let bits = (0..16).map(|i| i % 4 < 2).collect();
+        let mask =
+            Shape::Mask(Mask::new(4, 4, bits).fit(Rect::new(0_f32, 100_f32, 0_f32, 100_f32)));
+        assert!(mask.contains_block(&block(5_f32, 45_f32, 5_f32, 95_f32)));
+        assert!(!mask.contains_block(&block(5_f32, 55_f32, 5_f32, 95_f32)));
+    }
+}
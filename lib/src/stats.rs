@@ -0,0 +1,155 @@
+use serde::Serialize;
+
+use crate::block::Block;
+use crate::Orientation;
+use crate::Point2d;
+use crate::Rect;
+
+/// Measures of the quality of a layout.
+///
+/// See `Grid::stats`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LayoutStats {
+    /// Number of blocks placed.
+    pub placed: usize,
+    /// Number of blocks that could not be placed.
+    pub dropped: usize,
+    /// Area covered by the blocks divided by the area of the bounding rectangle.
+    ///
+    /// Overlapping blocks, as allowed by glyph level collisions, are counted
+    /// once.
+    pub fill_ratio: f32,
+    /// Mean number of candidates generated for each block placed.
+    pub mean_attempts: f32,
+    /// Area weighted centroid of the blocks, relative to the center of the canvas.
+    pub centroid_offset: Point2d,
+    /// Number of blocks with horizontal text.
+    pub horizontal: usize,
+    /// Number of blocks with text running downwards.
+    pub vertical90: usize,
+    /// Number of blocks with text running upwards.
+    pub vertical270: usize,
+}
+
+impl LayoutStats {
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn new(
+        blocks: &[Block],
+        dropped: usize,
+        attempts: usize,
+        canvas: &Rect,
+        bounds: &Rect,
+    ) -> Self {
+        let placed = blocks.len();
+        let total_area: f32 = blocks.iter().map(|b| b.bounds().area()).sum();
+
+        let center = canvas.center();
+        let centroid_offset = if total_area > 0_f32 {
+            let (x, y) = blocks.iter().fold((0_f32, 0_f32), |(x, y), b| {
+                let bounds = b.bounds();
+                let c = bounds.center();
+                let area = bounds.area();
+                (
+                    area.mul_add(c.x - center.x, x),
+                    area.mul_add(c.y - center.y, y),
+                )
+            });
+            Point2d {
+                x: x / total_area,
+                y: y / total_area,
+            }
+        } else {
+            Point2d { x: 0_f32, y: 0_f32 }
+        };
+
+        let count = |o: &Orientation| blocks.iter().filter(|b| b.orientation == *o).count();
+
+        Self {
+            placed,
+            dropped,
+            fill_ratio: union_area(&blocks.iter().map(Block::bounds).collect::<Vec<_>>())
+                / bounds.area(),
+            mean_attempts: if placed == 0 {
+                0_f32
+            } else {
+                attempts as f32 / placed as f32
+            },
+            centroid_offset,
+            horizontal: count(&Orientation::Horizontal),
+            vertical90: count(&Orientation::Vertical90),
+            vertical270: count(&Orientation::Vertical270),
+        }
+    }
+
+    /// Fraction of the blocks offered that were placed.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn placement_rate(&self) -> f32 {
+        let offered = self.placed + self.dropped;
+        if offered == 0 {
+            1_f32
+        } else {
+            self.placed as f32 / offered as f32
+        }
+    }
+}
+
+// Area covered by the rectangles, counting overlaps once.
+//
+// The plane is cut into vertical slabs at every left and right edge, within
+// a slab the covered height is the union of the spanning intervals.
+fn union_area(rects: &[Rect]) -> f32 {
+    let mut xs = rects
+        .iter()
+        .flat_map(|rect| [rect.xmin, rect.xmax])
+        .collect::<Vec<_>>();
+    xs.sort_by(f32::total_cmp);
+    xs.dedup();
+
+    let mut area = 0_f32;
+    for slab in xs.windows(2) {
+        let (x0, x1) = (slab[0], slab[1]);
+        let mut spans = rects
+            .iter()
+            .filter(|rect| rect.xmin <= x0 && rect.xmax >= x1)
+            .map(|rect| (rect.ymin, rect.ymax))
+            .collect::<Vec<_>>();
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut height = 0_f32;
+        let mut covered: Option<(f32, f32)> = None;
+        for (y0, y1) in spans {
+            covered = match covered {
+                Some((start, end)) if y0 <= end => Some((start, end.max(y1))),
+                Some((start, end)) => {
+                    height += end - start;
+                    Some((y0, y1))
+                }
+                None => Some((y0, y1)),
+            };
+        }
+        if let Some((start, end)) = covered {
+            height += end - start;
+        }
+        area = height.mul_add(x1 - x0, area);
+    }
+    area
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overlaps_count_once() {
+        let rects = [
+            Rect::new(0_f32, 10_f32, 0_f32, 10_f32),
+            Rect::new(5_f32, 15_f32, 5_f32, 15_f32),
+            // Inside the first.
+            Rect::new(2_f32, 4_f32, 2_f32, 4_f32),
+            Rect::new(20_f32, 30_f32, 0_f32, 1_f32),
+        ];
+        assert!((union_area(&rects) - 185_f32).abs() < 1e-3);
+        assert!(union_area(&[]).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,54 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Presentation of a word, carried from the input to the renderers.
+///
+/// Unset values are left to the renderer and any style sheet.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Style {
+    /// Fill colour, any SVG paint such as "#ff0000".
+    pub fill: Option<String>,
+    /// Opacity between 0 and 1.
+    pub opacity: Option<f32>,
+    /// Font family.
+    ///
+    /// The block is sized using Times New Roman metrics, whatever the family.
+    pub font_family: Option<String>,
+    /// Font weight such as "bold" or "700".
+    pub font_weight: Option<String>,
+    /// Slant the text.
+    pub italic: bool,
+}
+
+impl Style {
+    /// Returns a style setting only the fill.
+    #[must_use]
+    pub fn fill(fill: impl Into<String>) -> Self {
+        Self {
+            fill: Some(fill.into()),
+            ..Self::default()
+        }
+    }
+
+    // The presentation attributes of the text element, by SVG name.
+    pub(crate) fn attributes(&self) -> Vec<(&'static str, String)> {
+        let mut attributes = vec![];
+        if let Some(fill) = &self.fill {
+            attributes.push(("fill", fill.clone()));
+        }
+        if let Some(opacity) = self.opacity {
+            attributes.push(("opacity", opacity.to_string()));
+        }
+        if let Some(font_family) = &self.font_family {
+            attributes.push(("font-family", font_family.clone()));
+        }
+        if let Some(font_weight) = &self.font_weight {
+            attributes.push(("font-weight", font_weight.clone()));
+        }
+        if self.italic {
+            attributes.push(("font-style", String::from("italic")));
+        }
+        attributes
+    }
+}
@@ -0,0 +1,374 @@
+use core::fmt::Write;
+
+use crate::block::content_bounds;
+use crate::block::Block;
+use crate::Orientation;
+use crate::Rect;
+
+/// Controls the SVG elements emitted for a block.
+///
+/// Shared by the string renderer and, with the `leptos` feature, the leptos
+/// views.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    /// Emit the bounding rectangle.
+    pub show_bounds: bool,
+    /// Emit circles marking the two definition points.
+    pub show_anchors: bool,
+    /// Class of the text element.
+    pub text_class: Option<String>,
+    /// Class of the bounding rectangle.
+    pub bounds_class: Option<String>,
+    /// Classes of the bottom left and top right circles.
+    pub anchor_classes: [String; 2],
+    /// Fill of the text element, unless the block has its own.
+    pub fill: Option<String>,
+    /// Stroke of the text element.
+    pub stroke: Option<String>,
+    /// Crop the view box to the blocks, leaving this margin on every side.
+    ///
+    /// `None` shows the whole canvas.
+    pub crop: Option<f32>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            show_bounds: false,
+            show_anchors: false,
+            text_class: None,
+            bounds_class: None,
+            anchor_classes: [String::from("bl"), String::from("tr")],
+            fill: None,
+            stroke: None,
+            crop: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Returns options showing the construction of each block.
+    #[must_use]
+    pub fn blueprint() -> Self {
+        Self {
+            show_bounds: true,
+            show_anchors: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Returns the SVG elements representing a block.
+///
+/// The bounding rectangle and circles come first, so the text is drawn on
+/// top. A block with a title has a `<title>` child, shown as a tooltip, and a
+/// block with a link is wrapped in `<a xlink:href="...">`; the root element
+/// then needs `xmlns:xlink="http://www.w3.org/1999/xlink"`.
+#[must_use]
+pub fn block(block: &Block, options: &RenderOptions) -> String {
+    let mut out = String::new();
+    let bounds = block.bounds();
+
+    if options.show_bounds {
+        let _ = write!(
+            out,
+            "<rect{} x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+            attribute("class", options.bounds_class.as_deref()),
+            bounds.xmin,
+            bounds.ymin,
+            bounds.width(),
+            bounds.height()
+        );
+    }
+
+    if options.show_anchors {
+        let [bl, tr] = &options.anchor_classes;
+        for (class, p) in [(bl, &block.bottom_left), (tr, &block.top_right)] {
+            let _ = write!(
+                out,
+                "<circle class=\"{}\" cx=\"{}\" cy=\"{}\" r=\"2\"/>",
+                escape(class),
+                p.x,
+                p.y
+            );
+        }
+    }
+
+    out.push_str(&text(block, options, ""));
+    out
+}
+
+/// Returns the area of the canvas to show, see `RenderOptions::crop`.
+///
+/// Cropped or not, the rectangle maps onto the `viewBox` attribute as
+/// "xmin ymin width height". The whole canvas is shown when there are no
+/// blocks.
+#[must_use]
+pub fn view_box(blocks: &[Block], canvas: &Rect, options: &RenderOptions) -> Rect {
+    options
+        .crop
+        .and_then(|margin| content_bounds(blocks).map(|bounds| bounds.inflate(margin)))
+        .unwrap_or(*canvas)
+}
+
+// The text element, wrapped in a link when the block has one.
+//
+// The animation elements are placed inside the text element.
+pub(crate) fn text(block: &Block, options: &RenderOptions, animation: &str) -> String {
+    let mut out = String::new();
+
+    // The style of the word takes precedence over the options.
+    let mut style = block.style.clone().unwrap_or_default();
+    style.fill = style.fill.or_else(|| options.fill.clone());
+    let presentation = style
+        .attributes()
+        .into_iter()
+        .map(|(name, value)| attribute(name, Some(&value)))
+        .collect::<String>();
+
+    if let Some(href) = &block.href {
+        let _ = write!(out, "<a xlink:href=\"{}\">", escape(href));
+    }
+    let _ = write!(
+        out,
+        "<text{} transform=\"{}\"{}{} font-size=\"{}\">",
+        attribute("class", options.text_class.as_deref()),
+        transform(block),
+        presentation,
+        attribute("stroke", options.stroke.as_deref()),
+        block.font_size,
+    );
+    if let Some(title) = &block.title {
+        let _ = write!(out, "<title>{}</title>", escape(title));
+    }
+    let _ = write!(out, "{animation}{}</text>", escape(&block.text));
+    if block.href.is_some() {
+        out.push_str("</a>");
+    }
+    out
+}
+
+// Moves the start of the baseline, and rotates the text, into place.
+pub(crate) fn transform(block: &Block) -> String {
+    let anchor = block.baseline_origin();
+    let angle = match block.orientation {
+        Orientation::Horizontal => 0,
+        Orientation::Vertical90 => 90,
+        Orientation::Vertical270 => 270,
+    };
+    format!("translate({}, {}) rotate({angle})", anchor.x, anchor.y)
+}
+
+// An attribute, preceded by a space, or nothing.
+fn attribute(name: &str, value: Option<&str>) -> String {
+    value.map_or_else(String::new, |value| {
+        format!(" {name}=\"{}\"", escape(value))
+    })
+}
+
+// Escape text for use in content or attribute values.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use nom::bytes::complete::{is_not, tag, take_while1};
+    use nom::character::complete::{char, digit1, multispace1};
+    use nom::multi::many0;
+    use nom::number::complete::float;
+    use nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
+    use nom::IResult;
+
+    use super::*;
+    use crate::style::Style;
+    use crate::Point2d;
+
+    type Attributes<'a> = Vec<(&'a str, &'a str)>;
+
+    // The name and attributes of the first element.
+    fn start_tag(input: &str) -> IResult<&str, (&str, Attributes<'_>)> {
+        let name = take_while1(char::is_alphanumeric);
+        let attribute = separated_pair(
+            take_while1(|c: char| c.is_alphanumeric() || c == '-' || c == '_'),
+            char('='),
+            delimited(char('"'), is_not("\""), char('"')),
+        );
+        preceded(
+            char('<'),
+            pair(name, many0(preceded(multispace1, attribute))),
+        )(input)
+    }
+
+    // The translation and rotation of a transform attribute.
+    fn translate_rotate(input: &str) -> IResult<&str, (f32, f32, &str)> {
+        let (input, (_, x, _, y, _, angle, _)) = tuple((
+            tag("translate("),
+            float,
+            tag(", "),
+            float,
+            tag(") rotate("),
+            digit1,
+            char(')'),
+        ))(input)?;
+        Ok((input, (x, y, angle)))
+    }
+
+    // Checks the first text element of each rendered orientation.
+    pub fn assert_text_attributes<F>(render: F)
+    where
+        F: Fn(&Block) -> String,
+    {
+        let origin = Point2d {
+            x: 200_f32,
+            y: 300_f32,
+        };
+        for (orientation, angle) in [
+            (Orientation::Horizontal, "0"),
+            (Orientation::Vertical90, "90"),
+            (Orientation::Vertical270, "270"),
+        ] {
+            let b = Block::new(String::from("Word"), 2000_f32, &origin, orientation);
+            let svg = render(&b);
+            let text = &svg[svg.find("<text").expect("a text element")..];
+            let (_, (name, attributes)) = start_tag(text).expect("a start tag");
+            assert_eq!(name, "text");
+
+            let value = |key| attributes.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+            assert_eq!(value("font_size"), None);
+            let font_size: f32 = value("font-size")
+                .expect("a font-size")
+                .parse()
+                .expect("a number");
+            assert!((font_size - b.font_size).abs() < 1e-4);
+            // The rectangle is rotated with the text.
+            let thickness = match b.orientation {
+                Orientation::Horizontal => b.bounds().height(),
+                Orientation::Vertical90 | Orientation::Vertical270 => b.bounds().width(),
+            };
+            assert!((font_size - thickness).abs() < 1e-3);
+
+            // The text is anchored on the baseline, raised above the origin
+            // of the block to make room for descenders.
+            let (_, (x, y, a)) =
+                translate_rotate(value("transform").expect("a transform")).expect("a transform");
+            let descent = b.descent();
+            let (dx, dy) = match b.orientation {
+                Orientation::Horizontal => (0_f32, -descent),
+                Orientation::Vertical90 => (descent, 0_f32),
+                Orientation::Vertical270 => (-descent, 0_f32),
+            };
+            assert!(descent > 0_f32);
+            assert!((x - origin.x - dx).abs() < 1e-3 && (y - origin.y - dy).abs() < 1e-3);
+            assert_eq!(a, angle);
+        }
+    }
+
+    #[test]
+    fn text_attributes_for_every_orientation() {
+        assert_text_attributes(|b| block(b, &RenderOptions::default()));
+    }
+
+    #[test]
+    fn overlays_follow_options() {
+        let b = Block::new(
+            String::from("A&B"),
+            1000_f32,
+            &Point2d {
+                x: 100_f32,
+                y: 100_f32,
+            },
+            Orientation::Horizontal,
+        );
+
+        let plain = block(&b, &RenderOptions::default());
+        assert!(plain.starts_with("<text transform=\"translate(100, "));
+        assert!(plain.ends_with(">A&amp;B</text>"));
+        assert!(!plain.contains("<rect"));
+        assert!(!plain.contains("<circle"));
+
+        let options = RenderOptions {
+            fill: Some(String::from("red")),
+            ..RenderOptions::blueprint()
+        };
+        let blueprint = block(&b, &options);
+        assert!(blueprint.starts_with("<rect x=\"100\""));
+        assert_eq!(blueprint.matches("<circle").count(), 2);
+        assert!(blueprint.contains("class=\"bl\""));
+        assert!(blueprint.contains(" fill=\"red\""));
+
+        // The style of the block wins over the options.
+        let styled = Block {
+            style: Some(Style {
+                italic: true,
+                ..Style::fill("blue")
+            }),
+            ..b
+        };
+        let styled = block(&styled, &options);
+        assert!(styled.contains(" fill=\"blue\""));
+        assert!(!styled.contains(" fill=\"red\""));
+        assert!(styled.contains(" font-style=\"italic\""));
+    }
+
+    #[test]
+    fn crop_to_content() {
+        let canvas = Rect::new(0_f32, 800_f32, 0_f32, 600_f32);
+        let blocks = [(100_f32, 200_f32), (300_f32, 400_f32)].map(|(x, y)| {
+            Block::new(
+                String::from("word"),
+                400_f32,
+                &Point2d { x, y },
+                Orientation::Horizontal,
+            )
+        });
+        let options = RenderOptions {
+            crop: Some(10_f32),
+            ..RenderOptions::default()
+        };
+
+        let cropped = view_box(&blocks, &canvas, &options);
+        let content = content_bounds(&blocks).expect("two blocks");
+        assert_eq!(cropped, content.inflate(10_f32));
+        assert!((cropped.xmin - 90_f32).abs() < 1e-3);
+        assert!((cropped.ymax - 410_f32).abs() < 1e-3);
+
+        assert_eq!(view_box(&[], &canvas, &options), canvas);
+        assert_eq!(
+            view_box(&blocks, &canvas, &RenderOptions::default()),
+            canvas
+        );
+    }
+
+    #[test]
+    fn link_and_tooltip() {
+        let b = Block {
+            href: Some(String::from("/search?q=A&B")),
+            title: Some(String::from("12 uses")),
+            ..Block::new(
+                String::from("A&B"),
+                1000_f32,
+                &Point2d {
+                    x: 100_f32,
+                    y: 100_f32,
+                },
+                Orientation::Horizontal,
+            )
+        };
+        let svg = block(&b, &RenderOptions::blueprint());
+        assert!(svg.contains("<a xlink:href=\"/search?q=A&amp;B\"><text "));
+        assert!(svg.ends_with("><title>12 uses</title>A&amp;B</text></a>"));
+        // The overlays are not part of the link.
+        assert!(svg.starts_with("<rect "));
+    }
+}
@@ -0,0 +1,237 @@
+use core::fmt::Write;
+use core::time::Duration;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use crate::block::Block;
+use crate::svg::{self, RenderOptions};
+use crate::Point2d;
+
+/// What happens to a word between two layouts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// The word is in both layouts, with the same orientation.
+    ///
+    /// The block slides, and grows or shrinks, from one to the other.
+    Move {
+        /// The block in the first layout.
+        from: Box<Block>,
+        /// The block in the second layout.
+        to: Box<Block>,
+    },
+    /// The word only appears in the second layout, or changes orientation.
+    FadeIn(Block),
+    /// The word only appears in the first layout, or changes orientation.
+    FadeOut(Block),
+}
+
+/// A plan animating one layout into another.
+///
+/// Words are matched by their text, repeated words in the order placed.
+///
+/// ```text
+/// let transition = Transition::new(&before, &grid.blocks);
+/// // For a canvas, draw a frame at each tick.
+/// let blocks = transition.frame(0.5_f32);
+/// // Or let the SVG animate itself.
+/// let svg = transition.svg(&RenderOptions::default(), Duration::from_millis(500));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transition {
+    /// The changes, those fading out first so they are drawn underneath.
+    pub changes: Vec<Change>,
+}
+
+impl Transition {
+    /// Returns the plan taking the first layout to the second.
+    #[must_use]
+    pub fn new(before: &[Block], after: &[Block]) -> Self {
+        let mut unmatched: HashMap<&str, VecDeque<usize>> = HashMap::new();
+        for (i, block) in before.iter().enumerate() {
+            unmatched.entry(&block.text).or_default().push_back(i);
+        }
+
+        let mut fade_out = vec![];
+        let mut changes = vec![];
+        for to in after {
+            let from = unmatched
+                .get_mut(to.text.as_str())
+                .and_then(VecDeque::pop_front);
+            match from {
+                Some(i) if before[i].orientation == to.orientation => changes.push(Change::Move {
+                    from: Box::new(before[i].clone()),
+                    to: Box::new(to.clone()),
+                }),
+                Some(i) => {
+                    fade_out.push(i);
+                    changes.push(Change::FadeIn(to.clone()));
+                }
+                None => changes.push(Change::FadeIn(to.clone())),
+            }
+        }
+        // Keep the order of the first layout.
+        fade_out.extend(unmatched.into_values().flatten());
+        fade_out.sort_unstable();
+
+        let mut all = fade_out
+            .into_iter()
+            .map(|i| Change::FadeOut(before[i].clone()))
+            .collect::<Vec<_>>();
+        all.append(&mut changes);
+        Self { changes: all }
+    }
+
+    /// Returns the blocks to draw at `t`, from 0 for the first layout to 1
+    /// for the second.
+    ///
+    /// Positions and font-sizes change linearly, apply any easing to `t`.
+    /// Fading blocks carry their opacity in their style.
+    #[must_use]
+    pub fn frame(&self, t: f32) -> Vec<Block> {
+        let t = t.clamp(0_f32, 1_f32);
+        self.changes
+            .iter()
+            .filter_map(|change| match change {
+                Change::Move { from, to } => Some(Block {
+                    top_right: lerp_point(&from.top_right, &to.top_right, t),
+                    bottom_left: lerp_point(&from.bottom_left, &to.bottom_left, t),
+                    font_size: lerp(from.font_size, to.font_size, t),
+                    ..(**to).clone()
+                }),
+                Change::FadeIn(block) => Some(fade(block, t)),
+                Change::FadeOut(block) => (t < 1_f32).then(|| fade(block, 1_f32 - t)),
+            })
+            .collect()
+    }
+
+    /// Returns SVG text elements animated with SMIL.
+    ///
+    /// The animations begin when the document loads, and hold their final
+    /// state. The overlays selected by the options are not drawn.
+    #[must_use]
+    pub fn svg(&self, options: &RenderOptions, duration: Duration) -> String {
+        let dur = format!("{}s", duration.as_secs_f32());
+        let mut out = String::new();
+        for change in &self.changes {
+            match change {
+                Change::Move { from, to } => {
+                    let start = from.baseline_origin();
+                    let end = to.baseline_origin();
+                    let font_size = format!(
+                        "<animate attributeName=\"font-size\" from=\"{}\" to=\"{}\" dur=\"{dur}\" fill=\"freeze\"/>",
+                        from.font_size, to.font_size
+                    );
+                    let _ = write!(
+                        out,
+                        "<g><animateTransform attributeName=\"transform\" type=\"translate\" from=\"{} {}\" to=\"0 0\" dur=\"{dur}\" fill=\"freeze\"/>{}</g>",
+                        start.x - end.x,
+                        start.y - end.y,
+                        svg::text(to, options, &font_size)
+                    );
+                }
+                Change::FadeIn(block) => {
+                    let _ = write!(
+                        out,
+                        "<g opacity=\"0\"><animate attributeName=\"opacity\" from=\"0\" to=\"1\" dur=\"{dur}\" fill=\"freeze\"/>{}</g>",
+                        svg::text(block, options, "")
+                    );
+                }
+                Change::FadeOut(block) => {
+                    let _ = write!(
+                        out,
+                        "<g><animate attributeName=\"opacity\" from=\"1\" to=\"0\" dur=\"{dur}\" fill=\"freeze\"/>{}</g>",
+                        svg::text(block, options, "")
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+// A copy of the block with its opacity scaled, unchanged when fully visible.
+fn fade(block: &Block, alpha: f32) -> Block {
+    let mut block = block.clone();
+    if alpha < 1_f32 {
+        let style = block.style.get_or_insert_with(Default::default);
+        style.opacity = Some(style.opacity.unwrap_or(1_f32) * alpha);
+    }
+    block
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    (b - a).mul_add(t, a)
+}
+
+fn lerp_point(a: &Point2d, b: &Point2d, t: f32) -> Point2d {
+    Point2d {
+        x: lerp(a.x, b.x, t),
+        y: lerp(a.y, b.y, t),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Orientation;
+
+    fn block(text: &str, x: f32, orientation: Orientation) -> Block {
+        Block::new(
+            String::from(text),
+            2000_f32,
+            &Point2d { x, y: 300_f32 },
+            orientation,
+        )
+    }
+
+    #[test]
+    fn plan_and_frames() {
+        let before = vec![
+            block("stay", 100_f32, Orientation::Horizontal),
+            block("leave", 200_f32, Orientation::Horizontal),
+            block("turn", 300_f32, Orientation::Horizontal),
+        ];
+        let after = vec![
+            block("turn", 300_f32, Orientation::Vertical90),
+            block("stay", 500_f32, Orientation::Horizontal),
+            block("arrive", 600_f32, Orientation::Horizontal),
+        ];
+        let transition = Transition::new(&before, &after);
+        let kinds = transition
+            .changes
+            .iter()
+            .map(|change| match change {
+                Change::Move { to, .. } => ("move", to.text.as_str()),
+                Change::FadeIn(block) => ("in", block.text.as_str()),
+                Change::FadeOut(block) => ("out", block.text.as_str()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("out", "leave"),
+                ("out", "turn"),
+                ("in", "turn"),
+                ("move", "stay"),
+                ("in", "arrive"),
+            ]
+        );
+
+        // The ends of the transition are the two layouts.
+        let start = transition.frame(0_f32);
+        assert_eq!(start[3].bottom_left, before[0].bottom_left);
+        assert_eq!(start[4].style.as_ref().and_then(|s| s.opacity), Some(0_f32));
+        assert_eq!(transition.frame(1_f32), after);
+
+        let middle = transition.frame(0.5_f32);
+        assert!((middle[3].bottom_left.x - 300_f32).abs() < 1e-3);
+        assert_eq!(
+            middle[0].style.as_ref().and_then(|s| s.opacity),
+            Some(0.5_f32)
+        );
+
+        let svg = transition.svg(&RenderOptions::default(), Duration::from_millis(500));
+        assert_eq!(svg.matches("<text").count(), 5);
+        assert!(svg.contains("type=\"translate\" from=\"-400 0\" to=\"0 0\" dur=\"0.5s\""));
+    }
+}
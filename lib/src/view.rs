@@ -0,0 +1,266 @@
+use leptos::component;
+use leptos::create_memo;
+use leptos::ev::MouseEvent;
+use leptos::svg;
+use leptos::view;
+use leptos::window;
+use leptos::Callable;
+use leptos::Callback;
+use leptos::CollectView;
+use leptos::IntoView;
+use leptos::MaybeSignal;
+use leptos::Signal;
+use leptos::SignalGet;
+use leptos::SignalWith;
+use leptos::View;
+
+use crate::block::Block;
+use crate::grid::Grid;
+use crate::layout::LayoutOptions;
+use crate::layout::Word;
+use crate::svg::transform;
+use crate::svg::view_box;
+use crate::svg::RenderOptions;
+use crate::Rect;
+
+impl IntoView for Block {
+    /// Returns a series of SVG elements
+    ///
+    ///```text
+    /// view!{
+    /// <For each=move || blocks.get() key=|block| { block.text.clone() } let:b>
+    /// {b.into_view()}
+    /// </For>
+    /// </svg>
+    ///}
+    ///}}
+    /// ```
+    fn into_view(self) -> View {
+        render_block(&self, None, &RenderOptions::blueprint())
+    }
+}
+
+/// A word cloud, laid out again whenever its inputs change.
+///
+/// ```text
+/// let (words, words_set) = create_signal(vec![Word::new("apple", 2)]);
+/// view! {
+///     <WordMap words=words width=800_f32 height=600_f32/>
+/// }
+/// ```
+#[component]
+#[must_use]
+pub fn WordMap(
+    /// The words to place.
+    #[prop(into)]
+    words: Signal<Vec<Word>>,
+    /// Width of the canvas.
+    #[prop(default = 800_f32.into(), into)]
+    width: MaybeSignal<f32>,
+    /// Height of the canvas.
+    #[prop(default = 600_f32.into(), into)]
+    height: MaybeSignal<f32>,
+    /// Controls the size and placement of the words.
+    #[prop(optional, into)]
+    options: MaybeSignal<LayoutOptions>,
+    /// Returns the fill colour of a block, overriding its style.
+    #[prop(optional, into)]
+    color: Option<Callback<Block, String>>,
+    /// Lays out the words identically for a given seed.
+    #[prop(optional)]
+    seed: Option<u64>,
+    /// Controls the elements emitted for each block.
+    #[prop(optional)]
+    render_options: RenderOptions,
+    /// Called with the block clicked, after any link is followed.
+    #[prop(optional, into)]
+    on_click: Option<Callback<Block>>,
+) -> impl IntoView {
+    let blocks = create_memo(move |_| {
+        let (width, height) = (width.get(), height.get());
+        let mut grid = seed.map_or_else(
+            || Grid::new(width, height),
+            |seed| Grid::new_with_seed(width, height, seed),
+        );
+        words.with(|words| options.with(|options| grid.layout(words, options)));
+        grid.blocks
+    });
+    let crop = render_options.clone();
+    let shown = create_memo(move |_| {
+        let canvas = Rect::new(0_f32, width.get(), 0_f32, height.get());
+        blocks.with(|blocks| view_box(blocks, &canvas, &crop))
+    });
+
+    view! {
+        <svg
+            class="word_map"
+            width=move || shown.get().width()
+            height=move || shown.get().height()
+            viewBox=move || {
+                let rect = shown.get();
+                format!("{} {} {} {}", rect.xmin, rect.ymin, rect.width(), rect.height())
+            }
+            version="1.1"
+            xmlns="http://www.w3.org/2000/svg"
+        >
+            {move || {
+                blocks
+                    .get()
+                    .iter()
+                    .map(|block| {
+                        let fill = color.map(|color| color.call(block.clone()));
+                        let view = render_block(block, fill, &render_options);
+                        match on_click {
+                            Some(on_click) => {
+                                let block = block.clone();
+                                view! {
+                                    <g on:click=move |_| on_click.call(block.clone())>{view}</g>
+                                }
+                                .into_view()
+                            }
+                            None => view,
+                        }
+                    })
+                    .collect_view()
+            }}
+        </svg>
+    }
+}
+
+/// Returns the text of the block, with any overlays selected by the options.
+///
+/// A fill given here takes precedence over the style of the block, which
+/// takes precedence over the options. Clicking a block with a link follows
+/// it, and a block with a title shows it as a tooltip.
+pub fn render_block(block: &Block, fill: Option<String>, options: &RenderOptions) -> View {
+    let bounds = block.bounds();
+    let style = block.style.clone().unwrap_or_default();
+    let fill = fill.or(style.fill).or_else(|| options.fill.clone());
+    let href = block.href.clone();
+    let cursor = href.as_ref().map(|_| "pointer");
+    let follow = move |_: MouseEvent| {
+        if let Some(href) = &href {
+            let _ = window().location().set_href(href);
+        }
+    };
+    // Built directly, as `view!` would make a lone title an HTML element.
+    let title = block.title.clone().map(|title| svg::title().child(title));
+
+    let text = view! {
+        <text
+            class=options.text_class.clone()
+            transform=transform(block)
+            fill=fill
+            opacity=style.opacity
+            font-family=style.font_family
+            font-weight=style.font_weight
+            font-style=style.italic.then_some("italic")
+            stroke=options.stroke.clone()
+            font-size=block.font_size
+            cursor=cursor
+            on:click=follow
+        >
+            {title}
+            {block.text.clone()}
+        </text>
+    };
+
+    let rect = options.show_bounds.then(|| {
+        view! {
+            <rect
+                class=options.bounds_class.clone()
+                x=bounds.xmin
+                y=bounds.ymin
+                width=bounds.width()
+                height=bounds.height()
+            ></rect>
+        }
+    });
+    let anchors = options.show_anchors.then(|| {
+        let [bl, tr] = options.anchor_classes.clone();
+        view! {
+            <circle class=bl cx=block.bottom_left.x cy=block.bottom_left.y r="2"></circle>
+            <circle class=tr cx=block.top_right.x cy=block.top_right.y r="2"></circle>
+        }
+    });
+
+    view! {
+        {rect}
+        {anchors}
+        {text}
+    }
+    .into_view()
+}
+
+#[cfg(test)]
+mod test {
+    use leptos::create_runtime;
+    use leptos::create_signal;
+    use leptos::SignalSet;
+
+    use super::*;
+    use crate::svg::test::assert_text_attributes;
+
+    #[test]
+    fn text_attributes_for_every_orientation() {
+        let runtime = create_runtime();
+        assert_text_attributes(|block| {
+            render_block(block, None, &RenderOptions::default())
+                .render_to_string()
+                .to_string()
+        });
+        runtime.dispose();
+    }
+
+    #[test]
+    fn word_map_follows_its_signals() {
+        let runtime = create_runtime();
+        let (words, words_set) = create_signal(vec![Word::new("apple", 2), Word::new("pear", 5)]);
+        let (width, width_set) = create_signal(800_f32);
+        // Away from the browser a view is rendered as it is built, so each
+        // check builds the map again.
+        let render = || {
+            view! { <WordMap words=words width=width seed=7/> }
+                .into_view()
+                .render_to_string()
+                .to_string()
+        };
+        let view_box = |html: &str| {
+            let start = html.find("viewBox=\"").unwrap_or_default() + 9;
+            html[start..]
+                .split('"')
+                .next()
+                .unwrap_or_default()
+                .to_owned()
+        };
+        // The transform and word of each text element, in the order drawn.
+        let texts = |html: &str| {
+            html.split("<text")
+                .skip(1)
+                .map(|text| {
+                    let text = text.split("</text>").next().unwrap_or_default();
+                    let start = text.find("transform=\"").unwrap_or_default() + 11;
+                    let transform = text[start..].split('"').next().unwrap_or_default();
+                    let word = text.split("-->").last().unwrap_or_default();
+                    format!("{transform} {word}")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = render();
+        assert_eq!(view_box(&first), "0 0 800 600");
+        assert!(texts(&first).iter().any(|text| text.contains("apple")));
+
+        words_set.set(vec![Word::new("cherry", 2), Word::new("pear", 5)]);
+        let second = render();
+        assert!(texts(&second).iter().any(|text| text.contains("cherry")));
+        assert!(!texts(&second).iter().any(|text| text.contains("apple")));
+
+        width_set.set(500_f32);
+        let third = render();
+        assert_eq!(view_box(&third), "0 0 500 600");
+        assert_eq!(texts(&third).len(), 2);
+        assert_ne!(texts(&third), texts(&second));
+        runtime.dispose();
+    }
+}
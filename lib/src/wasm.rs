@@ -0,0 +1,165 @@
+use wasm_bindgen::prelude::*;
+
+use crate::grid::Grid;
+use crate::layout::LayoutOptions;
+use crate::layout::Word;
+use crate::Point2d;
+use crate::Rect;
+
+/// A grid usable from JavaScript.
+///
+/// ```text
+/// import init, { WordMap } from "./word_map.js";
+///
+/// await init();
+/// const map = new WordMap(800, 600);
+/// map.place([["apple", 2], ["bubble", 10]]);
+/// const blocks = map.blocks();
+/// ```
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct WordMap {
+    grid: Grid,
+    options: LayoutOptions,
+}
+
+#[wasm_bindgen]
+impl WordMap {
+    /// Returns an empty map given the dimension of the canvas/svg.
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            grid: Grid::new(width, height),
+            options: LayoutOptions::default(),
+        }
+    }
+
+    /// Returns an empty map that lays out blocks identically for a given seed.
+    #[wasm_bindgen(js_name = withSeed)]
+    #[must_use]
+    pub fn with_seed(width: f32, height: f32, seed: u32) -> Self {
+        Self {
+            grid: Grid::new_with_seed(width, height, u64::from(seed)),
+            options: LayoutOptions::default(),
+        }
+    }
+
+    /// Limit the placement of new blocks to a sub rectangle of the canvas.
+    ///
+    /// # Errors
+    ///
+    /// When the rectangle is empty or leaves the canvas.
+    #[wasm_bindgen(js_name = setBounds)]
+    pub fn set_bounds(
+        &mut self,
+        xmin: f32,
+        xmax: f32,
+        ymin: f32,
+        ymax: f32,
+    ) -> Result<(), JsError> {
+        self.grid
+            .try_bounding_rectangle_set(Rect::new(xmin, xmax, ymin, ymax))?;
+        Ok(())
+    }
+
+    /// Set the font-size given to the lowest and highest weights.
+    #[wasm_bindgen(js_name = setFontSizeRange)]
+    pub fn set_font_size_range(&mut self, min: f32, max: f32) {
+        self.options.size_scale = self
+            .options
+            .size_scale
+            .clone()
+            .font_size_range_set([min, max]);
+    }
+
+    /// Place an array of `[text, weight]` pairs, largest weight first.
+    ///
+    /// Returns the number of blocks placed.
+    ///
+    /// # Errors
+    ///
+    /// When the value is not an array of pairs.
+    pub fn place(&mut self, pairs: JsValue) -> Result<usize, JsError> {
+        let pairs: Vec<(String, u32)> = serde_wasm_bindgen::from_value(pairs)?;
+        let words = pairs
+            .into_iter()
+            .map(|(text, weight)| Word::new(text, weight))
+            .collect::<Vec<_>>();
+        Ok(self.grid.layout(&words, &self.options).placed)
+    }
+
+    /// Place an array of words, largest weight first.
+    ///
+    /// Each word is an object such as
+    /// `{ text: "apple", weight: 2, style: { fill: "red" } }`, the style is
+    /// optional.
+    ///
+    /// Returns the number of blocks placed.
+    ///
+    /// # Errors
+    ///
+    /// When the value is not an array of words.
+    #[wasm_bindgen(js_name = placeWords)]
+    pub fn place_words(&mut self, words: JsValue) -> Result<usize, JsError> {
+        let words: Vec<Word> = serde_wasm_bindgen::from_value(words)?;
+        Ok(self.grid.layout(&words, &self.options).placed)
+    }
+
+    /// Place pairs written as text, for example "apple,2 bubble,10".
+    ///
+    /// Returns the number of blocks placed.
+    ///
+    /// # Errors
+    ///
+    /// When the text cannot be parsed.
+    #[wasm_bindgen(js_name = placeText)]
+    pub fn place_text(&mut self, input: &str) -> Result<usize, JsError> {
+        let (_, pairs) =
+            Grid::parse_pairs(input).map_err(|e| JsError::new(&format!("invalid pairs {e}")))?;
+        let words = pairs.iter().map(Word::from).collect::<Vec<_>>();
+        Ok(self.grid.layout(&words, &self.options).placed)
+    }
+
+    /// Returns the placed blocks as an array of objects.
+    ///
+    /// # Errors
+    ///
+    /// When the blocks cannot be converted.
+    pub fn blocks(&self) -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(&self.grid.blocks)?)
+    }
+
+    /// Scale the layout uniformly onto a canvas of another size, centred.
+    pub fn letterbox(&mut self, width: f32, height: f32) {
+        self.grid = self.grid.letterbox(width, height);
+    }
+
+    /// Place the blocks again on a canvas of another size, near their
+    /// previous relative positions.
+    pub fn relayout(&mut self, width: f32, height: f32) {
+        self.grid = self.grid.relayout(width, height, self.options.max_attempts);
+    }
+
+    /// Returns the block under the point, or `undefined`.
+    ///
+    /// # Errors
+    ///
+    /// When the block cannot be converted.
+    #[wasm_bindgen(js_name = blockAt)]
+    pub fn block_at(&self, x: f32, y: f32) -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(
+            &self.grid.block_at(&Point2d { x, y }),
+        )?)
+    }
+
+    /// Returns the placed blocks as a JSON string.
+    ///
+    /// # Errors
+    ///
+    /// When the blocks cannot be converted.
+    #[wasm_bindgen(js_name = blocksJson)]
+    pub fn blocks_json(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.grid.blocks)?)
+    }
+}